@@ -0,0 +1,114 @@
+//! The terminal bell, rung by the application or by a `BEL` byte written to the backend.
+
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::{window, AudioContext, HtmlCanvasElement, OscillatorType};
+
+use crate::dom::Hook;
+
+/// The shortest time between two bells in milliseconds. Bells rung sooner are dropped, so a
+/// burst of them cannot strobe the screen.
+pub const BELL_INTERVAL: f64 = 200.0;
+
+/// How long the canvas stays flashed, in milliseconds.
+const FLASH_DURATION: i32 = 100;
+
+/// The pitch of the beep in hertz.
+const BEEP_FREQUENCY: f32 = 880.0;
+
+/// How long the beep lasts, in seconds.
+const BEEP_DURATION: f64 = 0.12;
+
+/// How the bell is signalled.
+#[derive(Debug)]
+pub(crate) struct Bell {
+    /// Whether the canvas flashes.
+    pub(crate) visual: bool,
+    /// Whether a short beep is played.
+    pub(crate) audible: bool,
+    /// When the last bell rang, in milliseconds since the epoch.
+    last: f64,
+    /// Created on the first beep, since browsers only allow audio after a user gesture.
+    audio: Option<AudioContext>,
+    /// Called whenever the bell rings.
+    pub(crate) on_bell: Hook<()>,
+}
+
+impl Default for Bell {
+    fn default() -> Self {
+        Bell {
+            visual: true,
+            audible: false,
+            last: f64::NEG_INFINITY,
+            audio: None,
+            on_bell: Hook::default(),
+        }
+    }
+}
+
+impl Bell {
+    /// Rings the bell, flashing `canvas` unless `reduced_motion` is set, unless it rang less
+    /// than [`BELL_INTERVAL`] ago.
+    pub(crate) fn ring(&mut self, canvas: &HtmlCanvasElement, reduced_motion: bool) {
+        let now = js_sys::Date::now();
+        if now - self.last < BELL_INTERVAL {
+            return;
+        }
+        self.last = now;
+
+        if self.visual && !reduced_motion {
+            flash(canvas);
+        }
+        if self.audible {
+            self.beep();
+        }
+        self.on_bell.emit(());
+    }
+
+    /// Plays a short sine tone that fades out.
+    fn beep(&mut self) {
+        if self.audio.is_none() {
+            self.audio = AudioContext::new().ok();
+        }
+        let Some(audio) = &self.audio else {
+            return;
+        };
+        // A context created before any user gesture starts suspended.
+        let _ = audio.resume();
+
+        let (Ok(oscillator), Ok(gain)) = (audio.create_oscillator(), audio.create_gain()) else {
+            return;
+        };
+        let start = audio.current_time();
+        oscillator.set_type(OscillatorType::Sine);
+        oscillator.frequency().set_value(BEEP_FREQUENCY);
+        let volume = gain.gain();
+        let _ = volume.set_value_at_time(0.2, start);
+        let _ = volume.exponential_ramp_to_value_at_time(0.001, start + BEEP_DURATION);
+
+        let _ = oscillator
+            .connect_with_audio_node(&gain)
+            .and_then(|_| gain.connect_with_audio_node(&audio.destination()));
+        let _ = oscillator.start_with_when(start);
+        let _ = oscillator.stop_with_when(start + BEEP_DURATION);
+    }
+}
+
+/// Inverts the colours of `canvas` for [`FLASH_DURATION`] through a CSS filter, which leaves
+/// its pixels alone.
+fn flash(canvas: &HtmlCanvasElement) {
+    let style = canvas.style();
+    let filter = style.get_property_value("filter").unwrap_or_default();
+    if style.set_property("filter", "invert(1)").is_err() {
+        return;
+    }
+
+    let restore = Closure::once_into_js(move || {
+        let _ = style.set_property("filter", &filter);
+    });
+    let _ = window()
+        .unwrap()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(
+            restore.unchecked_ref(),
+            FLASH_DURATION,
+        );
+}
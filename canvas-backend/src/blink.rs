@@ -0,0 +1,69 @@
+//! Animation of cells with [`Modifier::SLOW_BLINK`] and [`Modifier::RAPID_BLINK`].
+
+use std::{cell::RefCell, rc::Rc};
+
+use ratatui::style::Modifier;
+use web_sys::window;
+
+use crate::{
+    dom::{Interval, Listener},
+    screen::Screen,
+};
+
+/// How often rapidly blinking cells toggle, in milliseconds.
+const TICK: i32 = 150;
+/// How many ticks slowly blinking cells stay in each phase.
+const SLOW_TICKS: u32 = 4;
+
+const REDUCED_MOTION: &str = "(prefers-reduced-motion: reduce)";
+
+/// The modifiers that make a cell blink.
+pub(crate) const BLINKING: Modifier = Modifier::SLOW_BLINK.union(Modifier::RAPID_BLINK);
+
+/// The current phase of blinking cells.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Blink {
+    tick: u32,
+    slow_off: bool,
+    rapid_off: bool,
+    /// Whether the user asked for reduced motion, in which case nothing blinks.
+    pub(crate) reduced_motion: bool,
+}
+
+impl Blink {
+    /// Returns `true` if the glyph of a cell with `modifier` is hidden in the current phase.
+    pub(crate) fn hides(&self, modifier: Modifier) -> bool {
+        !self.reduced_motion
+            && (modifier.contains(Modifier::RAPID_BLINK) && self.rapid_off
+                || modifier.contains(Modifier::SLOW_BLINK) && self.slow_off)
+    }
+
+    /// Moves on to the next phase.
+    pub(crate) fn advance(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+        self.rapid_off = !self.rapid_off;
+        self.slow_off = self.tick / SLOW_TICKS % 2 == 1;
+    }
+}
+
+/// Starts blinking the cells of `screen` on a timer.
+pub(crate) fn animate(screen: &Rc<RefCell<Screen>>) -> Interval {
+    let screen = Rc::clone(screen);
+    Interval::new(TICK, move || screen.borrow_mut().blink())
+}
+
+/// Tracks the `prefers-reduced-motion` media query, stopping all blinking while it matches.
+pub(crate) fn listen_reduced_motion(screen: &Rc<RefCell<Screen>>) -> Option<Listener> {
+    let query = window().unwrap().match_media(REDUCED_MOTION).ok()??;
+    screen.borrow_mut().set_reduced_motion(query.matches());
+
+    let screen = Rc::clone(screen);
+    let target = query.clone();
+    Some(Listener::new(
+        &target,
+        "change",
+        move |_: web_sys::Event| {
+            screen.borrow_mut().set_reduced_motion(query.matches());
+        },
+    ))
+}
@@ -0,0 +1,129 @@
+//! Pasting text into the application and copying text to the system clipboard.
+
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    io,
+    rc::Rc,
+};
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    window, ClipboardEvent, Document, HtmlDocument, HtmlElement, HtmlTextAreaElement, KeyboardEvent,
+};
+
+use crate::{dom::Listener, event::Event, screen::Screen};
+
+thread_local! {
+    /// Whether text is being copied through a hidden text area, which has focus meanwhile.
+    static COPYING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Returns `true` while text is being copied through a hidden text area, so the focus moving
+/// to it and back is not reported.
+pub(crate) fn copying() -> bool {
+    COPYING.with(Cell::get)
+}
+
+fn document() -> Document {
+    window().unwrap().document().unwrap()
+}
+
+/// Returns `true` if `event` is a shortcut the browser pastes with, which is reported as the
+/// pasted text rather than as a key.
+pub(crate) fn is_paste_key(event: &KeyboardEvent) -> bool {
+    let key = event.key();
+    let v = key.eq_ignore_ascii_case("v") && !event.alt_key();
+    (v && (event.ctrl_key() || event.meta_key())) || (key == "Insert" && event.shift_key())
+}
+
+/// Returns `true` if `event` is a shortcut the browser copies with, which copies the selection
+/// rather than being reported as a key while there is one.
+pub(crate) fn is_copy_key(event: &KeyboardEvent) -> bool {
+    let key = event.key();
+    let c = key.eq_ignore_ascii_case("c") && !event.alt_key();
+    (c && (event.ctrl_key() || event.meta_key())) || (key == "Insert" && event.ctrl_key())
+}
+
+/// Reports text pasted while the canvas has focus and the application takes input as a single
+/// paste event, like a terminal with bracketed paste.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Listener {
+    let screen = Rc::clone(screen);
+    Listener::active(&document(), "paste", move |event: ClipboardEvent| {
+        let screen = screen.borrow();
+        if !screen.capture || !screen.has_focus() {
+            return;
+        }
+        let Some(text) = event
+            .clipboard_data()
+            .and_then(|data| data.get_data("text/plain").ok())
+            .filter(|text| !text.is_empty())
+        else {
+            return;
+        };
+        event.prevent_default();
+        screen.on_event.emit(Event::Paste(text));
+    })
+}
+
+/// Writes `text` to the system clipboard.
+///
+/// The async Clipboard API is only available in secure contexts, and may be denied, e.g.
+/// while the document is not focused. Either way the text is copied through a hidden text
+/// area instead.
+pub(crate) fn write(text: &str) -> impl Future<Output = io::Result<()>> + 'static {
+    let clipboard = window().unwrap().navigator().clipboard();
+    let pending = if clipboard.is_undefined() {
+        Err(copy_selected(text))
+    } else {
+        Ok(JsFuture::from(clipboard.write_text(text)))
+    };
+    let text = text.to_string();
+    async move {
+        match pending {
+            Ok(written) => match written.await {
+                Ok(_) => Ok(()),
+                Err(_) => copy_selected(&text),
+            },
+            Err(result) => result,
+        }
+    }
+}
+
+/// Copies `text` with `document.execCommand("copy")` on a hidden text area, which works in
+/// older browsers and insecure contexts as long as it runs in response to user input.
+fn copy_selected(text: &str) -> io::Result<()> {
+    let failed = || io::Error::other("copying to the clipboard failed");
+    let document = document();
+    let area = document
+        .create_element("textarea")
+        .map_err(|_| failed())?
+        .dyn_into::<HtmlTextAreaElement>()
+        .map_err(|_| failed())?;
+    area.set_value(text);
+    let _ = area.set_attribute("readonly", "");
+    let _ = area.set_attribute("aria-hidden", "true");
+    let style = area.style();
+    let _ = style.set_property("position", "fixed");
+    let _ = style.set_property("top", "0");
+    let _ = style.set_property("left", "0");
+    let _ = style.set_property("opacity", "0");
+    let body = document.body().ok_or_else(failed)?;
+    body.append_child(&area).map_err(|_| failed())?;
+
+    COPYING.with(|copying| copying.set(true));
+    let focused = document.active_element();
+    area.select();
+    let copied = document
+        .dyn_ref::<HtmlDocument>()
+        .and_then(|document| document.exec_command("copy").ok())
+        .unwrap_or(false);
+    area.remove();
+    if let Some(focused) = focused.and_then(|element| element.dyn_into::<HtmlElement>().ok()) {
+        let _ = focused.focus();
+    }
+    COPYING.with(|copying| copying.set(false));
+
+    copied.then_some(()).ok_or_else(failed)
+}
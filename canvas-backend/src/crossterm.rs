@@ -0,0 +1,27 @@
+//! crossterm's event types, for input handling written against `crossterm::event`.
+//!
+//! Enabled by the `crossterm` feature. On native targets, [`event`] is `crossterm::event`
+//! itself. crossterm always compiles its terminal layer, which does not build for
+//! `wasm32-unknown-unknown`, so there [`event`] is a copy of its event types instead, with the
+//! same names, fields, constructors and trait implementations. Handlers that name the types
+//! through this module compile unchanged for both:
+//!
+//! ```ignore
+//! use canvas_backend::crossterm::event::{Event, KeyCode, KeyEventKind};
+//!
+//! fn handle(app: &mut App, event: Event) {
+//!     if let Event::Key(key) = event {
+//!         if key.kind == KeyEventKind::Press && key.code == KeyCode::Char('q') {
+//!             app.quit();
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The events of [`crate::event`] convert into these types with [`From`].
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use ::crossterm::event;
+
+#[cfg(target_arch = "wasm32")]
+pub mod event;
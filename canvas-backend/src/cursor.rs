@@ -0,0 +1,67 @@
+//! The terminal cursor.
+
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{dom::Interval, screen::Screen};
+
+/// How often a blinking cursor toggles, in milliseconds.
+const BLINK_INTERVAL: i32 = 530;
+
+/// The shape the cursor is drawn in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CursorShape {
+    /// A block filling the whole cell, with the glyph under it drawn inverted.
+    #[default]
+    Block,
+    /// A line along the bottom of the cell.
+    Underline,
+    /// A line along the left edge of the cell.
+    Bar,
+}
+
+/// The position, visibility and look of the cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Cursor {
+    pub(crate) x: u16,
+    pub(crate) y: u16,
+    pub(crate) visible: bool,
+    pub(crate) shape: CursorShape,
+    pub(crate) blink: bool,
+    /// Whether a blinking cursor is in the phase where it is not drawn.
+    pub(crate) blinked_off: bool,
+    /// Whether the canvas has keyboard focus. The cursor is drawn hollow and does not blink
+    /// without it.
+    pub(crate) focused: bool,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Cursor {
+            x: 0,
+            y: 0,
+            visible: true,
+            shape: CursorShape::default(),
+            blink: false,
+            blinked_off: false,
+            focused: false,
+        }
+    }
+}
+
+impl Cursor {
+    /// Returns `true` if the cursor is drawn right now.
+    pub(crate) fn drawn(&self) -> bool {
+        self.visible && !(self.blink && self.focused && self.blinked_off)
+    }
+}
+
+/// Starts toggling the cursor of `screen` on a timer, unless the user prefers reduced motion.
+pub(crate) fn blink(screen: &Rc<RefCell<Screen>>) -> Interval {
+    let screen = Rc::clone(screen);
+    Interval::new(BLINK_INTERVAL, move || {
+        let mut screen = screen.borrow_mut();
+        if !screen.blink.reduced_motion {
+            screen.update_cursor(|cursor| cursor.blinked_off = !cursor.blinked_off);
+        }
+    })
+}
@@ -0,0 +1,219 @@
+//! Helpers for DOM event listeners and application callbacks.
+
+use std::{cell::RefCell, fmt, rc::Rc};
+
+use wasm_bindgen::{prelude::Closure, JsCast};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{window, AddEventListenerOptions, EventTarget};
+
+/// An event listener that is removed from its target when dropped.
+pub(crate) struct Listener {
+    target: EventTarget,
+    kind: &'static str,
+    closure: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+impl Listener {
+    /// Adds a passive listener for `kind` events to `target`.
+    pub(crate) fn new<E, F>(target: &EventTarget, kind: &'static str, handler: F) -> Listener
+    where
+        E: JsCast,
+        F: FnMut(E) + 'static,
+    {
+        Listener::with_passive(target, kind, true, handler)
+    }
+
+    /// Adds a listener for `kind` events to `target` that may call `preventDefault`.
+    pub(crate) fn active<E, F>(target: &EventTarget, kind: &'static str, handler: F) -> Listener
+    where
+        E: JsCast,
+        F: FnMut(E) + 'static,
+    {
+        Listener::with_passive(target, kind, false, handler)
+    }
+
+    fn with_passive<E, F>(
+        target: &EventTarget,
+        kind: &'static str,
+        passive: bool,
+        mut handler: F,
+    ) -> Listener
+    where
+        E: JsCast,
+        F: FnMut(E) + 'static,
+    {
+        let closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            handler(event.unchecked_into());
+        }) as Box<dyn FnMut(_)>);
+
+        let options = AddEventListenerOptions::new();
+        options.set_passive(passive);
+        target
+            .add_event_listener_with_callback_and_add_event_listener_options(
+                kind,
+                closure.as_ref().unchecked_ref(),
+                &options,
+            )
+            .unwrap();
+
+        Listener {
+            target: target.clone(),
+            kind,
+            closure,
+        }
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = self
+            .target
+            .remove_event_listener_with_callback(self.kind, self.closure.as_ref().unchecked_ref());
+    }
+}
+
+impl fmt::Debug for Listener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Listener")
+            .field("kind", &self.kind)
+            .finish()
+    }
+}
+
+/// A timer calling a closure every `timeout` milliseconds until dropped.
+pub(crate) struct Interval {
+    handle: i32,
+    _closure: Closure<dyn FnMut()>,
+}
+
+impl Interval {
+    pub(crate) fn new(timeout: i32, handler: impl FnMut() + 'static) -> Interval {
+        let closure = Closure::wrap(Box::new(handler) as Box<dyn FnMut()>);
+        let handle = window()
+            .unwrap()
+            .set_interval_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                timeout,
+            )
+            .unwrap();
+
+        Interval {
+            handle,
+            _closure: closure,
+        }
+    }
+}
+
+impl Drop for Interval {
+    fn drop(&mut self) {
+        window().unwrap().clear_interval_with_handle(self.handle);
+    }
+}
+
+impl fmt::Debug for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Interval")
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+/// A timer calling a closure once after `timeout` milliseconds, unless dropped before.
+pub(crate) struct Timeout {
+    handle: i32,
+    _closure: Closure<dyn FnMut()>,
+}
+
+impl Timeout {
+    pub(crate) fn new(timeout: i32, handler: impl FnOnce() + 'static) -> Timeout {
+        let mut handler = Some(handler);
+        let closure = Closure::wrap(Box::new(move || {
+            if let Some(handler) = handler.take() {
+                handler();
+            }
+        }) as Box<dyn FnMut()>);
+        let handle = window()
+            .unwrap()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(
+                closure.as_ref().unchecked_ref(),
+                timeout,
+            )
+            .unwrap();
+
+        Timeout {
+            handle,
+            _closure: closure,
+        }
+    }
+}
+
+impl Drop for Timeout {
+    fn drop(&mut self) {
+        window().unwrap().clear_timeout_with_handle(self.handle);
+    }
+}
+
+impl fmt::Debug for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Timeout")
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+type Handler<T> = Rc<RefCell<Option<Box<dyn FnMut(T)>>>>;
+
+/// A callback the application registers with the backend.
+///
+/// Calls are deferred to a microtask, so the callback is free to borrow the `Terminal` that
+/// owns the backend even when it is triggered from within one of the backend's methods.
+pub(crate) struct Hook<T> {
+    handler: Handler<T>,
+}
+
+impl<T: 'static> Hook<T> {
+    /// Replaces the callback.
+    pub(crate) fn set(&self, handler: impl FnMut(T) + 'static) {
+        *self.handler.borrow_mut() = Some(Box::new(handler));
+    }
+
+    /// Calls the callback with `value` once the current task has finished.
+    pub(crate) fn emit(&self, value: T) {
+        if matches!(self.handler.try_borrow().as_deref(), Ok(None)) {
+            return;
+        }
+        let handler = Rc::clone(&self.handler);
+        spawn_local(async move {
+            if let Some(handler) = handler.borrow_mut().as_mut() {
+                handler(value);
+            }
+        });
+    }
+}
+
+impl<T> Default for Hook<T> {
+    fn default() -> Self {
+        Hook {
+            handler: Rc::default(),
+        }
+    }
+}
+
+impl<T> Clone for Hook<T> {
+    fn clone(&self) -> Self {
+        Hook {
+            handler: Rc::clone(&self.handler),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Hook<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hook")
+            .field(
+                "set",
+                &self.handler.try_borrow().map_or(true, |h| h.is_some()),
+            )
+            .finish()
+    }
+}
@@ -0,0 +1,336 @@
+//! Typed input events translated from browser events.
+//!
+//! The types mirror the ones of crossterm, so key handling reads the same as with ratatui's
+//! native backends:
+//!
+//! ```ignore
+//! use canvas_backend::event::{KeyCode, KeyEvent, KeyEventKind};
+//!
+//! if let Some(key) = KeyEvent::from_keyboard_event(&event) {
+//!     if key.kind == KeyEventKind::Press {
+//!         match key.code {
+//!             KeyCode::Down | KeyCode::Char('j') => app.next(),
+//!             KeyCode::Up | KeyCode::Char('k') => app.previous(),
+//!             _ => {}
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The backend itself delivers an [`Event`] for every key, mouse, focus, paste and resize event
+//! to the callback registered with [`CanvasBackend::on_event`](crate::CanvasBackend::on_event),
+//! and to the [`EventStream`]s created with
+//! [`CanvasBackend::event_stream`](crate::CanvasBackend::event_stream).
+//!
+//! With the `crossterm` feature, the events also convert into their `crossterm::event`
+//! counterparts with [`From`], which [`crate::crossterm`] provides on the web as well.
+
+use bitflags::bitflags;
+use web_sys::KeyboardEvent;
+
+pub use stream::EventStream;
+pub(crate) use stream::Events;
+
+#[cfg(feature = "crossterm")]
+mod crossterm;
+mod stream;
+
+/// An input event.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// A key was pressed, repeated or released.
+    Key(KeyEvent),
+    /// The mouse was used over the grid, see
+    /// [`CanvasBackend::set_mouse_capture`](crate::CanvasBackend::set_mouse_capture).
+    Mouse(MouseEvent),
+    /// Text was pasted, e.g. with Ctrl+V or the browser's paste action, and is delivered at
+    /// once like with bracketed paste rather than as keys.
+    Paste(String),
+    /// The canvas gained keyboard focus.
+    FocusGained,
+    /// The canvas lost keyboard focus, e.g. to another element on the page or because the
+    /// browser window was left.
+    FocusLost,
+    /// The grid has a new number of columns and rows.
+    Resize(u16, u16),
+}
+
+/// A key pressed, repeated or released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyEvent {
+    /// The key.
+    pub code: KeyCode,
+    /// The modifier keys held down.
+    pub modifiers: KeyModifiers,
+    /// Whether the key was pressed, repeated or released.
+    pub kind: KeyEventKind,
+}
+
+impl KeyEvent {
+    /// Creates a key press of `code` with `modifiers`.
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new_with_kind(code, modifiers, KeyEventKind::Press)
+    }
+
+    /// Creates a key event of `code` with `modifiers` and `kind`.
+    pub const fn new_with_kind(
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        kind: KeyEventKind,
+    ) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind,
+        }
+    }
+
+    /// Translates a `keydown` or `keyup` event.
+    ///
+    /// Returns `None` for keys a terminal does not report on their own, i.e. modifier keys,
+    /// dead keys of a compose sequence and keys the browser cannot identify.
+    ///
+    /// Characters are reported as typed, so Shift+a is `Char('A')` with
+    /// [`KeyModifiers::SHIFT`], and characters typed through AltGr carry no Ctrl or Alt. On
+    /// macOS, Option+letter is reported as Alt with the letter rather than the character the
+    /// layout composes.
+    pub fn from_keyboard_event(event: &KeyboardEvent) -> Option<KeyEvent> {
+        let mut modifiers = KeyModifiers::from_keys(
+            event.shift_key(),
+            event.ctrl_key(),
+            event.alt_key(),
+            event.meta_key(),
+        );
+
+        let key = event.key();
+        let code = match key.as_str() {
+            "Backspace" => KeyCode::Backspace,
+            "Enter" => KeyCode::Enter,
+            "Tab" if event.shift_key() => KeyCode::BackTab,
+            "Tab" => KeyCode::Tab,
+            "Escape" | "Esc" => KeyCode::Esc,
+            "ArrowLeft" | "Left" => KeyCode::Left,
+            "ArrowRight" | "Right" => KeyCode::Right,
+            "ArrowUp" | "Up" => KeyCode::Up,
+            "ArrowDown" | "Down" => KeyCode::Down,
+            "Home" => KeyCode::Home,
+            "End" => KeyCode::End,
+            "PageUp" => KeyCode::PageUp,
+            "PageDown" => KeyCode::PageDown,
+            "Delete" | "Del" => KeyCode::Delete,
+            "Insert" => KeyCode::Insert,
+            "CapsLock" => KeyCode::CapsLock,
+            "ScrollLock" => KeyCode::ScrollLock,
+            "NumLock" => KeyCode::NumLock,
+            "PrintScreen" => KeyCode::PrintScreen,
+            "Pause" => KeyCode::Pause,
+            "ContextMenu" => KeyCode::Menu,
+            "Spacebar" => KeyCode::Char(' '),
+            key => {
+                if let Some(n) = key.strip_prefix('F').and_then(|n| n.parse().ok()) {
+                    KeyCode::F(n)
+                } else {
+                    let mut chars = key.chars();
+                    let (Some(c), None) = (chars.next(), chars.next()) else {
+                        return None;
+                    };
+                    if event.get_modifier_state("AltGraph") {
+                        // Windows reports AltGr as Ctrl+Alt.
+                        modifiers.remove(KeyModifiers::CONTROL | KeyModifiers::ALT);
+                        KeyCode::Char(c)
+                    } else if event.alt_key() && !event.ctrl_key() {
+                        KeyCode::Char(physical_char(&event.code(), event.shift_key()).unwrap_or(c))
+                    } else {
+                        KeyCode::Char(c)
+                    }
+                }
+            }
+        };
+
+        let kind = if event.type_() == "keyup" {
+            KeyEventKind::Release
+        } else if event.repeat() {
+            KeyEventKind::Repeat
+        } else {
+            KeyEventKind::Press
+        };
+
+        Some(KeyEvent {
+            code,
+            modifiers,
+            kind,
+        })
+    }
+}
+
+/// Returns the letter or digit printed on the physical key `code`, e.g. `"KeyA"`, as typed
+/// with or without `shift`.
+fn physical_char(code: &str, shift: bool) -> Option<char> {
+    let c = code
+        .strip_prefix("Key")
+        .or_else(|| code.strip_prefix("Digit"))?
+        .chars()
+        .next()?;
+    Some(if shift {
+        c.to_ascii_uppercase()
+    } else {
+        c.to_ascii_lowercase()
+    })
+}
+
+/// A key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    /// Backspace.
+    Backspace,
+    /// Enter.
+    Enter,
+    /// Left arrow.
+    Left,
+    /// Right arrow.
+    Right,
+    /// Up arrow.
+    Up,
+    /// Down arrow.
+    Down,
+    /// Home.
+    Home,
+    /// End.
+    End,
+    /// Page up.
+    PageUp,
+    /// Page down.
+    PageDown,
+    /// Tab.
+    Tab,
+    /// Shift+Tab.
+    BackTab,
+    /// Delete.
+    Delete,
+    /// Insert.
+    Insert,
+    /// A function key, e.g. `F(1)` for F1.
+    F(u8),
+    /// A character, e.g. `Char('a')` or `Char(' ')`.
+    Char(char),
+    /// Escape.
+    Esc,
+    /// Caps lock.
+    CapsLock,
+    /// Scroll lock.
+    ScrollLock,
+    /// Num lock.
+    NumLock,
+    /// Print screen.
+    PrintScreen,
+    /// Pause.
+    Pause,
+    /// The context menu key.
+    Menu,
+}
+
+bitflags! {
+    /// The modifier keys held down during an event.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct KeyModifiers: u8 {
+        /// Shift.
+        const SHIFT = 0b0000_0001;
+        /// Ctrl.
+        const CONTROL = 0b0000_0010;
+        /// Alt, or Option on macOS.
+        const ALT = 0b0000_0100;
+        /// Meta, i.e. Command on macOS and the Windows key elsewhere.
+        const META = 0b0000_1000;
+    }
+}
+
+impl KeyModifiers {
+    /// Returns the modifiers of an event, from its `shiftKey`, `ctrlKey`, `altKey` and
+    /// `metaKey`.
+    pub(crate) fn from_keys(shift: bool, control: bool, alt: bool, meta: bool) -> KeyModifiers {
+        let mut modifiers = KeyModifiers::empty();
+        modifiers.set(KeyModifiers::SHIFT, shift);
+        modifiers.set(KeyModifiers::CONTROL, control);
+        modifiers.set(KeyModifiers::ALT, alt);
+        modifiers.set(KeyModifiers::META, meta);
+        modifiers
+    }
+}
+
+/// Whether a key was pressed, repeated or released.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyEventKind {
+    /// The key was pressed.
+    #[default]
+    Press,
+    /// The key is held down and repeats.
+    Repeat,
+    /// The key was released.
+    Release,
+}
+
+/// A mouse event over the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MouseEvent {
+    /// What the mouse did.
+    pub kind: MouseEventKind,
+    /// The column of the cell under the pointer.
+    pub column: u16,
+    /// The row of the cell under the pointer.
+    pub row: u16,
+    /// The modifier keys held down.
+    pub modifiers: KeyModifiers,
+}
+
+/// What the mouse did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseEventKind {
+    /// A button was pressed.
+    Down(MouseButton),
+    /// A button was released.
+    Up(MouseButton),
+    /// The pointer moved onto another cell while a button was held down.
+    Drag(MouseButton),
+    /// The pointer moved onto another cell with no button held down.
+    Moved,
+    /// The wheel scrolled down, towards the user.
+    ScrollDown,
+    /// The wheel scrolled up, away from the user.
+    ScrollUp,
+    /// The wheel scrolled left.
+    ScrollLeft,
+    /// The wheel scrolled right.
+    ScrollRight,
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    /// The left, or primary, button.
+    Left,
+    /// The right, or secondary, button.
+    Right,
+    /// The middle button, often the wheel.
+    Middle,
+}
+
+impl MouseButton {
+    /// Returns the button of a DOM `button` number, e.g. `0` for the primary button.
+    pub(crate) fn from_dom(button: i16) -> Option<MouseButton> {
+        match button {
+            0 => Some(MouseButton::Left),
+            1 => Some(MouseButton::Middle),
+            2 => Some(MouseButton::Right),
+            _ => None,
+        }
+    }
+
+    /// Returns the bit of the button in a DOM `buttons` mask.
+    pub(crate) fn mask(self) -> u16 {
+        match self {
+            MouseButton::Left => 1,
+            MouseButton::Right => 2,
+            MouseButton::Middle => 4,
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! Conversions into the event types of crossterm.
+//!
+//! Enabled by the `crossterm` feature, so input handling written against
+//! `crossterm::event` runs unchanged on events translated from the browser. The types are
+//! the ones of [`crate::crossterm::event`], i.e. crossterm's own on native targets and a copy
+//! of them on the web.
+//!
+//! Browsers cannot produce everything crossterm can describe, so the following are never
+//! produced:
+//!
+//! - [`KeyCode::Null`](ct::KeyCode::Null), [`KeyCode::KeypadBegin`](ct::KeyCode::KeypadBegin),
+//!   [`KeyCode::Media`](ct::KeyCode::Media) and [`KeyCode::Modifier`](ct::KeyCode::Modifier),
+//!   as modifier keys are only reported through the modifiers of other keys.
+//! - [`KeyModifiers::HYPER`](ct::KeyModifiers::HYPER) and
+//!   [`KeyModifiers::META`](ct::KeyModifiers::META). The Command and Windows keys are reported
+//!   as [`KeyModifiers::SUPER`](ct::KeyModifiers::SUPER).
+//! - Any [`KeyEventState`](ct::KeyEventState) other than empty.
+//!
+//! On the web, the conversions produce the copied types rather than the ones of the
+//! `crossterm` crate, which does not build there. Handlers have to import the types through
+//! [`crate::crossterm::event`] instead of `crossterm::event`, and crates that take crossterm
+//! events themselves, such as widgets handling their own input, do not accept them.
+
+use crate::crossterm::event as ct;
+
+use super::{
+    Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+impl From<KeyCode> for ct::KeyCode {
+    fn from(code: KeyCode) -> Self {
+        match code {
+            KeyCode::Backspace => ct::KeyCode::Backspace,
+            KeyCode::Enter => ct::KeyCode::Enter,
+            KeyCode::Left => ct::KeyCode::Left,
+            KeyCode::Right => ct::KeyCode::Right,
+            KeyCode::Up => ct::KeyCode::Up,
+            KeyCode::Down => ct::KeyCode::Down,
+            KeyCode::Home => ct::KeyCode::Home,
+            KeyCode::End => ct::KeyCode::End,
+            KeyCode::PageUp => ct::KeyCode::PageUp,
+            KeyCode::PageDown => ct::KeyCode::PageDown,
+            KeyCode::Tab => ct::KeyCode::Tab,
+            KeyCode::BackTab => ct::KeyCode::BackTab,
+            KeyCode::Delete => ct::KeyCode::Delete,
+            KeyCode::Insert => ct::KeyCode::Insert,
+            KeyCode::F(n) => ct::KeyCode::F(n),
+            KeyCode::Char(c) => ct::KeyCode::Char(c),
+            KeyCode::Esc => ct::KeyCode::Esc,
+            KeyCode::CapsLock => ct::KeyCode::CapsLock,
+            KeyCode::ScrollLock => ct::KeyCode::ScrollLock,
+            KeyCode::NumLock => ct::KeyCode::NumLock,
+            KeyCode::PrintScreen => ct::KeyCode::PrintScreen,
+            KeyCode::Pause => ct::KeyCode::Pause,
+            KeyCode::Menu => ct::KeyCode::Menu,
+        }
+    }
+}
+
+impl From<KeyModifiers> for ct::KeyModifiers {
+    fn from(modifiers: KeyModifiers) -> Self {
+        let mut converted = ct::KeyModifiers::NONE;
+        converted.set(
+            ct::KeyModifiers::SHIFT,
+            modifiers.contains(KeyModifiers::SHIFT),
+        );
+        converted.set(
+            ct::KeyModifiers::CONTROL,
+            modifiers.contains(KeyModifiers::CONTROL),
+        );
+        converted.set(ct::KeyModifiers::ALT, modifiers.contains(KeyModifiers::ALT));
+        converted.set(
+            ct::KeyModifiers::SUPER,
+            modifiers.contains(KeyModifiers::META),
+        );
+        converted
+    }
+}
+
+impl From<KeyEventKind> for ct::KeyEventKind {
+    fn from(kind: KeyEventKind) -> Self {
+        match kind {
+            KeyEventKind::Press => ct::KeyEventKind::Press,
+            KeyEventKind::Repeat => ct::KeyEventKind::Repeat,
+            KeyEventKind::Release => ct::KeyEventKind::Release,
+        }
+    }
+}
+
+impl From<KeyEvent> for ct::KeyEvent {
+    fn from(key: KeyEvent) -> Self {
+        ct::KeyEvent::new_with_kind(key.code.into(), key.modifiers.into(), key.kind.into())
+    }
+}
+
+impl From<KeyEvent> for ct::Event {
+    fn from(key: KeyEvent) -> Self {
+        ct::Event::Key(key.into())
+    }
+}
+
+impl From<MouseButton> for ct::MouseButton {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => ct::MouseButton::Left,
+            MouseButton::Right => ct::MouseButton::Right,
+            MouseButton::Middle => ct::MouseButton::Middle,
+        }
+    }
+}
+
+impl From<MouseEventKind> for ct::MouseEventKind {
+    fn from(kind: MouseEventKind) -> Self {
+        match kind {
+            MouseEventKind::Down(button) => ct::MouseEventKind::Down(button.into()),
+            MouseEventKind::Up(button) => ct::MouseEventKind::Up(button.into()),
+            MouseEventKind::Drag(button) => ct::MouseEventKind::Drag(button.into()),
+            MouseEventKind::Moved => ct::MouseEventKind::Moved,
+            MouseEventKind::ScrollDown => ct::MouseEventKind::ScrollDown,
+            MouseEventKind::ScrollUp => ct::MouseEventKind::ScrollUp,
+            MouseEventKind::ScrollLeft => ct::MouseEventKind::ScrollLeft,
+            MouseEventKind::ScrollRight => ct::MouseEventKind::ScrollRight,
+        }
+    }
+}
+
+impl From<MouseEvent> for ct::MouseEvent {
+    fn from(mouse: MouseEvent) -> Self {
+        ct::MouseEvent {
+            kind: mouse.kind.into(),
+            column: mouse.column,
+            row: mouse.row,
+            modifiers: mouse.modifiers.into(),
+        }
+    }
+}
+
+impl From<Event> for ct::Event {
+    fn from(event: Event) -> Self {
+        match event {
+            Event::Key(key) => key.into(),
+            Event::Mouse(mouse) => ct::Event::Mouse(mouse.into()),
+            Event::Paste(text) => ct::Event::Paste(text),
+            Event::FocusGained => ct::Event::FocusGained,
+            Event::FocusLost => ct::Event::FocusLost,
+            Event::Resize(columns, rows) => ct::Event::Resize(columns, rows),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_key_events() {
+        let key = KeyEvent::new_with_kind(
+            KeyCode::Char('A'),
+            KeyModifiers::SHIFT | KeyModifiers::CONTROL,
+            KeyEventKind::Release,
+        );
+        assert_eq!(
+            ct::Event::from(Event::Key(key)),
+            ct::Event::Key(ct::KeyEvent::new_with_kind(
+                ct::KeyCode::Char('A'),
+                ct::KeyModifiers::SHIFT | ct::KeyModifiers::CONTROL,
+                ct::KeyEventKind::Release,
+            ))
+        );
+        assert_eq!(ct::KeyCode::from(KeyCode::F(12)), ct::KeyCode::F(12));
+        assert_eq!(ct::KeyCode::from(KeyCode::BackTab), ct::KeyCode::BackTab);
+    }
+
+    #[test]
+    fn converts_meta_to_super() {
+        assert_eq!(
+            ct::KeyModifiers::from(KeyModifiers::META | KeyModifiers::ALT),
+            ct::KeyModifiers::SUPER | ct::KeyModifiers::ALT
+        );
+        assert_eq!(
+            ct::KeyModifiers::from(KeyModifiers::empty()),
+            ct::KeyModifiers::NONE
+        );
+    }
+
+    #[test]
+    fn leaves_the_key_event_state_empty() {
+        let key = ct::KeyEvent::from(KeyEvent::new(KeyCode::Enter, KeyModifiers::empty()));
+        assert_eq!(key.state, ct::KeyEventState::NONE);
+    }
+
+    #[test]
+    fn converts_mouse_events() {
+        let mouse = MouseEvent {
+            kind: MouseEventKind::Drag(MouseButton::Middle),
+            column: 3,
+            row: 7,
+            modifiers: KeyModifiers::ALT,
+        };
+        assert_eq!(
+            ct::Event::from(Event::Mouse(mouse)),
+            ct::Event::Mouse(ct::MouseEvent {
+                kind: ct::MouseEventKind::Drag(ct::MouseButton::Middle),
+                column: 3,
+                row: 7,
+                modifiers: ct::KeyModifiers::ALT,
+            })
+        );
+        assert_eq!(
+            ct::MouseEventKind::from(MouseEventKind::ScrollLeft),
+            ct::MouseEventKind::ScrollLeft
+        );
+    }
+
+    #[test]
+    fn converts_other_events() {
+        assert_eq!(
+            ct::Event::from(Event::Paste("text".to_string())),
+            ct::Event::Paste("text".to_string())
+        );
+        assert_eq!(ct::Event::from(Event::FocusGained), ct::Event::FocusGained);
+        assert_eq!(ct::Event::from(Event::FocusLost), ct::Event::FocusLost);
+        assert_eq!(
+            ct::Event::from(Event::Resize(80, 24)),
+            ct::Event::Resize(80, 24)
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! An asynchronous stream of input events.
+
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    future::poll_fn,
+    io,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
+};
+
+use futures_core::{stream::FusedStream, Stream};
+
+use super::Event;
+use crate::dom::Hook;
+
+/// The events not yet taken from the [`EventStream`]s, and the task waiting for them.
+#[derive(Debug, Default)]
+pub(crate) struct Queue {
+    events: VecDeque<Event>,
+    waker: Option<Waker>,
+}
+
+impl Drop for Queue {
+    fn drop(&mut self) {
+        // Let a task waiting for an event see that the stream has ended.
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// Delivers input events to the callback registered with
+/// [`CanvasBackend::on_event`](crate::CanvasBackend::on_event) and to the event streams.
+#[derive(Debug, Default)]
+pub(crate) struct Events {
+    hook: Hook<Event>,
+    queue: Rc<RefCell<Queue>>,
+}
+
+impl Events {
+    /// Replaces the callback.
+    pub(crate) fn set(&self, handler: impl FnMut(Event) + 'static) {
+        self.hook.set(handler);
+    }
+
+    /// Queues `event` for the event streams, if there are any, and calls the callback with it
+    /// once the current task has finished.
+    pub(crate) fn emit(&self, event: Event) {
+        if Rc::weak_count(&self.queue) > 0 {
+            let waker = {
+                let mut queue = self.queue.borrow_mut();
+                queue.events.push_back(event.clone());
+                queue.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+        self.hook.emit(event);
+    }
+
+    /// Returns a stream of the events emitted from now on.
+    pub(crate) fn stream(&self) -> EventStream {
+        EventStream {
+            queue: Rc::downgrade(&self.queue),
+        }
+    }
+}
+
+/// The input events of a [`CanvasBackend`](crate::CanvasBackend) as a [`Stream`], created with
+/// [`CanvasBackend::event_stream`](crate::CanvasBackend::event_stream).
+///
+/// This is the counterpart of crossterm's `EventStream`, so an application can wait for
+/// events and timers alike in a task spawned with `wasm_bindgen_futures::spawn_local`:
+///
+/// ```ignore
+/// let mut events = terminal.backend().event_stream();
+/// let mut tick = IntervalStream::new(250).fuse();
+/// spawn_local(async move {
+///     loop {
+///         select! {
+///             event = events.next() => match event {
+///                 Some(Event::Key(key)) => app.on_key(key),
+///                 Some(_) => {}
+///                 None => break,
+///             },
+///             _ = tick.next() => app.on_tick(),
+///         }
+///         terminal.draw(|f| ui(f, &app)).unwrap();
+///     }
+/// });
+/// ```
+///
+/// Events are queued from the moment the stream is created until they are taken, and the
+/// callback registered with [`on_event`](crate::CanvasBackend::on_event) still receives them
+/// too. Streams of the same backend share the queue, so each event is taken by only one of
+/// them. The stream ends once the backend has been dropped.
+#[derive(Debug, Clone)]
+pub struct EventStream {
+    queue: Weak<RefCell<Queue>>,
+}
+
+impl EventStream {
+    /// Waits for the next event, like `crossterm::event::read` does natively.
+    ///
+    /// Fails with [`io::ErrorKind::Other`] once the backend has been dropped.
+    pub async fn next_event(&mut self) -> io::Result<Event> {
+        poll_fn(|cx| Pin::new(&mut *self).poll_next(cx))
+            .await
+            .ok_or_else(|| io::Error::other("the backend has been dropped"))
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Event>> {
+        let Some(queue) = self.queue.upgrade() else {
+            return Poll::Ready(None);
+        };
+        let mut queue = queue.borrow_mut();
+        match queue.events.pop_front() {
+            Some(event) => Poll::Ready(Some(event)),
+            None => {
+                queue.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl FusedStream for EventStream {
+    fn is_terminated(&self) -> bool {
+        self.queue.strong_count() == 0
+    }
+}
@@ -0,0 +1,87 @@
+//! Keyboard focus of the canvas.
+//!
+//! Keys are only taken while the canvas, or the IME text area standing in for it, has focus,
+//! so typing into other elements on the page does not reach the application, and only once
+//! the application has registered for events, so a canvas that merely displays output leaves
+//! the focus and the keys to the page.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{window, FocusEvent, FocusOptions, HtmlElement};
+
+use crate::{clipboard, dom::Listener, screen::Screen};
+
+/// Reports when the canvas of `screen` gains and loses focus, drawing the cursor hollow while
+/// it has none.
+///
+/// Focus moving between the canvas and the IME text area is not reported, nor is focus moving
+/// to the text area copying to the clipboard and back.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let document = window().unwrap().document().unwrap();
+    update(screen);
+
+    // Both bubble up from any element, unlike `focus` and `blur`. Leaving the browser window
+    // blurs the focused element as well.
+    let gained = {
+        let screen = Rc::clone(screen);
+        Listener::new(&document, "focusin", move |event: FocusEvent| {
+            if clipboard::copying() {
+                return;
+            }
+            let mut screen = screen.borrow_mut();
+            if screen.owns_focus(event.target()) {
+                screen.set_focused(true);
+            }
+        })
+    };
+
+    let lost = {
+        let screen = Rc::clone(screen);
+        Listener::new(&document, "focusout", move |event: FocusEvent| {
+            if clipboard::copying() {
+                return;
+            }
+            let mut screen = screen.borrow_mut();
+            if screen.owns_focus(event.target()) && !screen.owns_focus(event.related_target()) {
+                screen.set_focused(false);
+            }
+        })
+    };
+
+    vec![gained, lost]
+}
+
+/// Starts taking input for the application: makes the canvas of `screen` focusable and
+/// focuses it unless another element on the page has focus.
+pub(crate) fn capture(screen: &Rc<RefCell<Screen>>) {
+    let canvas = {
+        let mut screen = screen.borrow_mut();
+        if screen.capture {
+            return;
+        }
+        screen.capture = true;
+        screen.canvas()
+    };
+    // A tab index set by the page is kept, e.g. -1 to leave the canvas out of the tab order.
+    if !canvas.has_attribute("tabindex") {
+        let _ = canvas.set_attribute("tabindex", "0");
+    }
+    let document = window().unwrap().document().unwrap();
+    let body = document.body().map(HtmlElement::unchecked_into);
+    if document.active_element().is_none() || document.active_element() == body {
+        let options = FocusOptions::new();
+        options.set_prevent_scroll(true);
+        // The focus listeners borrow the screen, so it must not be borrowed here.
+        let _ = canvas.focus_with_options(&options);
+    }
+    update(screen);
+}
+
+/// Draws the cursor solid if the canvas has focus, without reporting it.
+fn update(screen: &Rc<RefCell<Screen>>) {
+    let document = window().unwrap().document().unwrap();
+    let mut screen = screen.borrow_mut();
+    let focused = document.has_focus().unwrap_or(true) && screen.has_focus();
+    screen.update_cursor(|cursor| cursor.focused = focused);
+}
@@ -0,0 +1,195 @@
+//! Font configuration and web font loading through the `FontFace` API.
+
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::{console, window, FontFace, FontFaceSet, FontFaceSetLoadStatus};
+
+use crate::screen::Screen;
+
+/// The font cells are rendered with.
+///
+/// Graphemes missing from [`family`](Font::family) are looked up in [`emoji`](Font::emoji),
+/// [`symbol`](Font::symbol) and then [`fallbacks`](Font::fallbacks), and glyphs taken from one
+/// of those are scaled and centred into their cells so the grid stays aligned.
+///
+/// Whether a family has a glyph is detected by rendering it, so a family whose glyph looks
+/// exactly like the one the browser's generic `serif` and `sans-serif` families both render
+/// counts as lacking it, and the next family is tried instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Font {
+    /// The primary font family, e.g. `"Iosevka"` or `"monospace"`.
+    pub family: String,
+    /// The font size in CSS pixels.
+    pub size: f64,
+    /// Families tried in order for graphemes the primary family has no glyph for.
+    pub fallbacks: Vec<String>,
+    /// The family emoji are rendered with.
+    pub emoji: Option<String>,
+    /// The family symbols such as math operators, arrows and private use icons are rendered
+    /// with when the primary family lacks them.
+    pub symbol: Option<String>,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Font {
+            family: "monospace".to_string(),
+            size: 16.0,
+            fallbacks: Vec::new(),
+            emoji: None,
+            symbol: None,
+        }
+    }
+}
+
+impl Font {
+    /// Returns the CSS `font` shorthand for the primary family and its fallbacks.
+    pub(crate) fn css(&self) -> String {
+        let mut families = vec![quote(&self.family)];
+        families.extend(self.fallbacks.iter().map(|family| quote(family)));
+        format!("{}px {}", self.size, families.join(", "))
+    }
+
+    /// Makes `family` the primary family, keeping the previous one as the first fallback
+    /// unless it is `family` itself or already a fallback. Every family is listed once.
+    pub(crate) fn set_family(&mut self, family: &str) {
+        let previous = std::mem::replace(&mut self.family, family.to_string());
+        self.fallbacks.retain(|fallback| fallback != family);
+        if previous != family && !self.fallbacks.contains(&previous) {
+            self.fallbacks.insert(0, previous);
+        }
+    }
+
+    /// Returns the CSS `font` shorthand for `family` alone, falling back to `generic`.
+    pub(crate) fn css_with(&self, family: &str, generic: &str) -> String {
+        format!("{}px {}, {generic}", self.size, quote(family))
+    }
+}
+
+/// Quotes `family` for use in a CSS font family list unless it is a generic family or
+/// already quoted.
+fn quote(family: &str) -> String {
+    const GENERIC: [&str; 9] = [
+        "serif",
+        "sans-serif",
+        "monospace",
+        "cursive",
+        "fantasy",
+        "system-ui",
+        "emoji",
+        "math",
+        "fangsong",
+    ];
+    if GENERIC.contains(&family) || family.starts_with(['"', '\'']) {
+        family.to_string()
+    } else {
+        format!("\"{}\"", family.replace('"', "\\\""))
+    }
+}
+
+/// Where a web font registered with [`CanvasBackend::load_font`] is loaded from.
+///
+/// [`CanvasBackend::load_font`]: crate::CanvasBackend::load_font
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FontSource {
+    /// A URL the browser fetches the font file from, e.g. `"/fonts/Iosevka.woff2"`.
+    Url(String),
+    /// The contents of a font file, e.g. from `include_bytes!`.
+    Bytes(Vec<u8>),
+}
+
+impl FontSource {
+    fn font_face(&self, family: &str) -> Result<FontFace, JsValue> {
+        match self {
+            FontSource::Url(url) => {
+                FontFace::new_with_str(family, &format!("url(\"{}\")", url.replace('"', "%22")))
+            }
+            FontSource::Bytes(bytes) => FontFace::new_with_u8_array(family, bytes),
+        }
+    }
+}
+
+/// Registers a font face for `family` with `document.fonts` and starts loading it.
+///
+/// Painting of `screen` is deferred until the face and every other font the document is
+/// waiting on have loaded, after which the cells are re-measured and repainted.
+pub(crate) fn load(screen: &Rc<RefCell<Screen>>, family: &str, source: &FontSource) {
+    let face = source
+        .font_face(family)
+        .and_then(|face| fonts().add(&face).map(|_| face));
+    match face {
+        Ok(face) => wait(screen, Some(face)),
+        Err(err) => console::error_2(&format!("failed to register font {family}").into(), &err),
+    }
+}
+
+/// Defers painting of `screen` until `face` (if any) and `document.fonts` have loaded.
+pub(crate) fn wait(screen: &Rc<RefCell<Screen>>, face: Option<FontFace>) {
+    screen.borrow_mut().pending_fonts += 1;
+
+    let screen = Rc::clone(screen);
+    spawn_local(async move {
+        if let Some(face) = face {
+            if let Err(err) = load_face(&face).await {
+                console::error_2(
+                    &format!("failed to load font {}", face.family()).into(),
+                    &err,
+                );
+            }
+        }
+        if let Err(err) = ready().await {
+            console::error_1(&err);
+        }
+
+        let mut screen = screen.borrow_mut();
+        screen.pending_fonts -= 1;
+        screen.remeasure();
+    });
+}
+
+/// Returns `true` if `document.fonts` is still loading fonts used by the page.
+pub(crate) fn loading() -> bool {
+    fonts().status() == FontFaceSetLoadStatus::Loading
+}
+
+/// Resolves once `document.fonts` has finished loading.
+pub(crate) async fn ready() -> Result<(), JsValue> {
+    JsFuture::from(fonts().ready()?).await.map(|_| ())
+}
+
+async fn load_face(face: &FontFace) -> Result<(), JsValue> {
+    JsFuture::from(face.load()?).await.map(|_| ())
+}
+
+fn fonts() -> FontFaceSet {
+    window().unwrap().document().unwrap().fonts()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_previous_family_as_a_fallback() {
+        let mut font = Font::default();
+        font.set_family("Iosevka");
+        font.set_family("Fira Code");
+        assert_eq!(font.family, "Fira Code");
+        assert_eq!(font.fallbacks, ["Iosevka", "monospace"]);
+    }
+
+    #[test]
+    fn keeps_each_fallback_once() {
+        let mut font = Font::default();
+        font.set_family("Iosevka");
+        font.set_family("Iosevka");
+        assert_eq!(font.fallbacks, ["monospace"]);
+
+        font.set_family("Fira Code");
+        font.set_family("Iosevka");
+        assert_eq!(font.family, "Iosevka");
+        assert_eq!(font.fallbacks, ["Fira Code", "monospace"]);
+    }
+}
@@ -0,0 +1,126 @@
+//! Per-grapheme font selection for glyphs the primary font lacks.
+
+use std::{cell::RefCell, collections::HashMap};
+
+use wasm_bindgen::JsCast;
+use web_sys::{window, CanvasRenderingContext2d, HtmlCanvasElement};
+
+use crate::font::Font;
+
+/// Picks the family each grapheme is rendered with and remembers which families have a glyph
+/// for which grapheme.
+#[derive(Debug)]
+pub(crate) struct Glyphs {
+    /// An offscreen canvas glyphs are rendered on to detect whether a family has them.
+    probe: CanvasRenderingContext2d,
+    coverage: RefCell<HashMap<(String, String), bool>>,
+}
+
+impl Glyphs {
+    pub(crate) fn new() -> Glyphs {
+        let canvas = window()
+            .unwrap()
+            .document()
+            .unwrap()
+            .create_element("canvas")
+            .unwrap()
+            .dyn_into::<HtmlCanvasElement>()
+            .unwrap();
+        let probe = canvas
+            .get_context("2d")
+            .unwrap()
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()
+            .unwrap();
+
+        Glyphs {
+            probe,
+            coverage: RefCell::default(),
+        }
+    }
+
+    /// Forgets which glyphs each family has, e.g. because a web font finished loading.
+    pub(crate) fn clear(&self) {
+        self.coverage.borrow_mut().clear();
+    }
+
+    /// Returns the family `grapheme` is rendered with, or `None` for the primary family.
+    pub(crate) fn fallback<'a>(&self, font: &'a Font, grapheme: &str) -> Option<&'a str> {
+        if grapheme.is_ascii() {
+            return None;
+        }
+        if let Some(emoji) = &font.emoji {
+            if is_emoji(grapheme) {
+                return Some(emoji);
+            }
+        }
+        if self.has_glyph(font, &font.family, grapheme) {
+            return None;
+        }
+        if let Some(symbol) = &font.symbol {
+            if is_symbol(grapheme) && self.has_glyph(font, symbol, grapheme) {
+                return Some(symbol);
+            }
+        }
+        font.fallbacks
+            .iter()
+            .find(|family| self.has_glyph(font, family, grapheme))
+            .map(String::as_str)
+    }
+
+    /// Returns `true` if `family` has a glyph for `grapheme`.
+    ///
+    /// Canvas offers no way to ask a font for its glyphs, so the grapheme is rendered with
+    /// `family` backed by a generic family and compared to the generic family alone. A family
+    /// that lacks the glyph lets the generic one through and renders identically, whether that
+    /// has the glyph or draws a missing glyph box. Both `serif` and `sans-serif` are tried, as
+    /// the glyph of `family` may happen to be the one a generic family resolves to.
+    fn has_glyph(&self, font: &Font, family: &str, grapheme: &str) -> bool {
+        let key = (family.to_string(), grapheme.to_string());
+        if let Some(&covered) = self.coverage.borrow().get(&key) {
+            return covered;
+        }
+
+        let covered = ["serif", "sans-serif"].into_iter().any(|generic| {
+            let generic_css = format!("{}px {generic}", font.size);
+            self.render(&font.css_with(family, generic), font.size, grapheme)
+                != self.render(&generic_css, font.size, grapheme)
+        });
+        self.coverage.borrow_mut().insert(key, covered);
+        covered
+    }
+
+    fn render(&self, css: &str, size: f64, grapheme: &str) -> Vec<u8> {
+        let canvas = self.probe.canvas().unwrap();
+        let extent = (size * 3.0).ceil() as u32;
+        if canvas.width() != extent {
+            canvas.set_width(extent);
+            canvas.set_height(extent);
+        }
+
+        let extent = f64::from(extent);
+        self.probe.clear_rect(0.0, 0.0, extent, extent);
+        self.probe.set_font(css);
+        self.probe.set_fill_style_str("#000000");
+        self.probe.fill_text(grapheme, size, size * 2.0).unwrap();
+        self.probe
+            .get_image_data(0.0, 0.0, extent, extent)
+            .map(|image| image.data().0)
+            .unwrap_or_default()
+    }
+}
+
+/// Returns `true` if `grapheme` is presented as an emoji.
+fn is_emoji(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .any(|c| c == '\u{fe0f}' || ('\u{1f000}'..='\u{1faff}').contains(&c))
+}
+
+/// Returns `true` if `grapheme` is a symbol, e.g. a math operator, an arrow or a private use
+/// icon.
+fn is_symbol(grapheme: &str) -> bool {
+    grapheme
+        .chars()
+        .any(|c| ('\u{2100}'..='\u{2bff}').contains(&c) || ('\u{e000}'..='\u{f8ff}').contains(&c))
+}
@@ -0,0 +1,275 @@
+//! Images placed over regions of cells.
+
+use std::{
+    cell::RefCell,
+    io,
+    rc::{Rc, Weak},
+};
+
+use ratatui::prelude::Rect;
+use wasm_bindgen::{Clamped, JsCast};
+use wasm_bindgen_futures::spawn_local;
+use web_sys::{
+    window, CanvasRenderingContext2d, HtmlCanvasElement, HtmlImageElement, ImageBitmap, ImageData,
+};
+
+use crate::{dom::Listener, screen::Screen};
+
+/// The pixels of an image to place over cells.
+#[derive(Debug, Clone)]
+pub enum ImageSource {
+    /// An `<img>` element. If it has not loaded yet, it is drawn once it has.
+    Element(HtmlImageElement),
+    /// A decoded bitmap, e.g. from `createImageBitmap`.
+    Bitmap(ImageBitmap),
+    /// Non-premultiplied RGBA pixels, four bytes per pixel, row by row.
+    Rgba {
+        /// The width of the image in pixels.
+        width: u32,
+        /// The height of the image in pixels.
+        height: u32,
+        /// The pixel data, `width * height * 4` bytes long.
+        data: Vec<u8>,
+    },
+}
+
+/// How an image is scaled to the cells it is placed over.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageFit {
+    /// Scales the image to fit inside the cells, keeping its aspect ratio and centring it.
+    #[default]
+    Fit,
+    /// Scales the image to cover the cells, keeping its aspect ratio and cropping what
+    /// overflows.
+    Fill,
+    /// Stretches the image to the cells, ignoring its aspect ratio.
+    Stretch,
+}
+
+/// An image placed over cells with [`CanvasBackend::place_image`].
+///
+/// The image is removed once this is dropped.
+///
+/// [`CanvasBackend::place_image`]: crate::CanvasBackend::place_image
+#[derive(Debug)]
+#[must_use = "the image is removed when the placement is dropped"]
+pub struct ImagePlacement {
+    screen: Weak<RefCell<Screen>>,
+    id: usize,
+}
+
+impl ImagePlacement {
+    /// Returns `true` if the image is still shown, i.e. no cell under it has been overwritten.
+    pub fn is_placed(&self) -> bool {
+        self.screen
+            .upgrade()
+            .is_some_and(|screen| screen.borrow().images.get(self.id).is_some())
+    }
+}
+
+impl Drop for ImagePlacement {
+    fn drop(&mut self) {
+        let Some(screen) = self.screen.upgrade() else {
+            return;
+        };
+        let id = self.id;
+        if let Ok(mut screen) = screen.try_borrow_mut() {
+            screen.remove_images(|placed, _| placed == id);
+            return;
+        }
+        // Dropped while the screen is borrowed, e.g. from a callback the backend runs, so
+        // remove the image once the current task has finished.
+        let screen = Rc::downgrade(&screen);
+        spawn_local(async move {
+            if let Some(screen) = screen.upgrade() {
+                screen.borrow_mut().remove_images(|placed, _| placed == id);
+            }
+        });
+    }
+}
+
+/// Something the context can draw.
+#[derive(Debug)]
+enum Drawable {
+    Element(HtmlImageElement),
+    Bitmap(ImageBitmap),
+    Canvas(HtmlCanvasElement),
+}
+
+impl Drawable {
+    /// Returns the natural size of the image, which is zero while it is loading.
+    fn size(&self) -> (f64, f64) {
+        let (width, height) = match self {
+            Drawable::Element(image) => (image.natural_width(), image.natural_height()),
+            Drawable::Bitmap(bitmap) => (bitmap.width(), bitmap.height()),
+            Drawable::Canvas(canvas) => (canvas.width(), canvas.height()),
+        };
+        (f64::from(width), f64::from(height))
+    }
+
+    fn draw(&self, context: &CanvasRenderingContext2d, x: f64, y: f64, w: f64, h: f64) {
+        let _ = match self {
+            Drawable::Element(image) => {
+                context.draw_image_with_html_image_element_and_dw_and_dh(image, x, y, w, h)
+            }
+            Drawable::Bitmap(bitmap) => {
+                context.draw_image_with_image_bitmap_and_dw_and_dh(bitmap, x, y, w, h)
+            }
+            Drawable::Canvas(canvas) => {
+                context.draw_image_with_html_canvas_element_and_dw_and_dh(canvas, x, y, w, h)
+            }
+        };
+    }
+}
+
+/// An image placed over a region of cells.
+#[derive(Debug)]
+pub(crate) struct Image {
+    pub(crate) area: Rect,
+    fit: ImageFit,
+    drawable: Drawable,
+    /// Repaints the image once its element has loaded.
+    _load: Option<Listener>,
+}
+
+impl Image {
+    /// Returns the device pixel rectangle the image is drawn into, given the device pixel
+    /// rectangle of its cells, as `(x, y, width, height)`.
+    pub(crate) fn destination(&self, cells: (f64, f64, f64, f64)) -> Option<(f64, f64, f64, f64)> {
+        let (left, top, right, bottom) = cells;
+        let (width, height) = (right - left, bottom - top);
+        let (natural_width, natural_height) = self.drawable.size();
+        if natural_width == 0.0 || natural_height == 0.0 {
+            return None;
+        }
+        let scale = match self.fit {
+            ImageFit::Stretch => return Some((left, top, width, height)),
+            ImageFit::Fit => (width / natural_width).min(height / natural_height),
+            ImageFit::Fill => (width / natural_width).max(height / natural_height),
+        };
+        let (w, h) = (natural_width * scale, natural_height * scale);
+        Some((left + (width - w) / 2.0, top + (height - h) / 2.0, w, h))
+    }
+
+    pub(crate) fn draw(&self, context: &CanvasRenderingContext2d, x: f64, y: f64, w: f64, h: f64) {
+        self.drawable.draw(context, x, y, w, h);
+    }
+}
+
+/// The images placed over the screen.
+#[derive(Debug, Default)]
+pub(crate) struct Images {
+    placed: Vec<(usize, Image)>,
+    next_id: usize,
+}
+
+impl Images {
+    pub(crate) fn get(&self, id: usize) -> Option<&Image> {
+        self.placed
+            .iter()
+            .find(|(placed, _)| *placed == id)
+            .map(|(_, image)| image)
+    }
+
+    /// Returns the images in the order they were placed, later ones drawn on top.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Image> {
+        self.placed.iter().map(|(_, image)| image)
+    }
+
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut Image> {
+        self.placed.iter_mut().map(|(_, image)| image)
+    }
+
+    /// Removes the images `remove` returns `true` for and returns their areas.
+    pub(crate) fn remove(&mut self, mut remove: impl FnMut(usize, &Image) -> bool) -> Vec<Rect> {
+        let mut removed = Vec::new();
+        self.placed.retain(|(id, image)| {
+            let keep = !remove(*id, image);
+            if !keep {
+                removed.push(image.area);
+            }
+            keep
+        });
+        removed
+    }
+}
+
+/// Places `source` over the cells of `area` of `screen`, scaled according to `fit`.
+pub(crate) fn place(
+    screen: &Rc<RefCell<Screen>>,
+    area: Rect,
+    source: ImageSource,
+    fit: ImageFit,
+) -> io::Result<ImagePlacement> {
+    let mut load = None;
+    let drawable = match source {
+        ImageSource::Element(image) => {
+            if !image.complete() {
+                let screen = Rc::downgrade(screen);
+                load = Some(Listener::new(&image, "load", move |_: web_sys::Event| {
+                    if let Some(screen) = screen.upgrade() {
+                        screen.borrow().repaint();
+                    }
+                }));
+            }
+            Drawable::Element(image)
+        }
+        ImageSource::Bitmap(bitmap) => Drawable::Bitmap(bitmap),
+        ImageSource::Rgba {
+            width,
+            height,
+            data,
+        } => Drawable::Canvas(rgba_canvas(width, height, &data)?),
+    };
+
+    let mut screen_ref = screen.borrow_mut();
+    let images = &mut screen_ref.images;
+    let id = images.next_id;
+    images.next_id += 1;
+    images.placed.push((
+        id,
+        Image {
+            area,
+            fit,
+            drawable,
+            _load: load,
+        },
+    ));
+    screen_ref.repaint_area(area);
+
+    Ok(ImagePlacement {
+        screen: Rc::downgrade(screen),
+        id,
+    })
+}
+
+/// Copies RGBA pixels onto an offscreen canvas, which the context can draw scaled.
+fn rgba_canvas(width: u32, height: u32, data: &[u8]) -> io::Result<HtmlCanvasElement> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_string());
+    if width == 0 || height == 0 || data.len() as u64 != u64::from(width) * u64::from(height) * 4 {
+        return Err(invalid("RGBA data does not match the image size"));
+    }
+    let image = ImageData::new_with_u8_clamped_array_and_sh(Clamped(data), width, height)
+        .map_err(|_| invalid("RGBA data does not match the image size"))?;
+
+    let canvas = window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into::<HtmlCanvasElement>()
+        .unwrap();
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let context = canvas
+        .get_context("2d")
+        .unwrap()
+        .unwrap()
+        .dyn_into::<CanvasRenderingContext2d>()
+        .unwrap();
+    context
+        .put_image_data(&image, 0.0, 0.0)
+        .map_err(|_| io::Error::other("failed to copy RGBA data"))?;
+    Ok(canvas)
+}
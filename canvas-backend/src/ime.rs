@@ -0,0 +1,256 @@
+//! Text input through input method editors, e.g. for Chinese, Japanese and Korean, and
+//! virtual keyboards.
+//!
+//! Key events never carry composed text, so a hidden text area placed at the cursor takes the
+//! input instead. The text being composed is drawn at the cursor, and the committed text is
+//! reported as a key or a paste.
+
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use unicode_width::UnicodeWidthChar;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, CompositionEvent, FocusEvent, FocusOptions, HtmlElement, HtmlTextAreaElement,
+    InputEvent, KeyboardEvent,
+};
+
+use crate::{
+    dom::Listener,
+    event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    screen::Screen,
+};
+
+/// The hidden text area and the text being composed in it.
+#[derive(Debug, Default)]
+pub(crate) struct Ime {
+    /// The text area taking the input, while IME input is enabled.
+    input: Option<HtmlTextAreaElement>,
+    /// The text being composed, drawn at the cursor.
+    pub(crate) preedit: String,
+}
+
+impl Ime {
+    /// Returns `true` if `element` is the hidden text area.
+    pub(crate) fn owns(&self, element: &HtmlElement) -> bool {
+        self.input
+            .as_ref()
+            .is_some_and(|input| input.unchecked_ref::<HtmlElement>() == element)
+    }
+
+    /// Moves the text area to `x`, `y` in CSS pixels relative to the viewport, a cell of
+    /// `height` high, so the candidate window of the IME opens next to the cursor.
+    pub(crate) fn follow(&self, x: f64, y: f64, height: f64) {
+        let Some(input) = &self.input else {
+            return;
+        };
+        let window = window().unwrap();
+        let left = x + window.scroll_x().unwrap_or_default();
+        let top = y + window.scroll_y().unwrap_or_default();
+        let style = input.style();
+        let _ = style.set_property("left", &format!("{left}px"));
+        let _ = style.set_property("top", &format!("{top}px"));
+        let _ = style.set_property("height", &format!("{height}px"));
+        let _ = style.set_property("font-size", &format!("{height}px"));
+    }
+}
+
+/// Moves keyboard focus to `element` without scrolling it into view.
+///
+/// Focus listeners borrow the screen, so it must not be borrowed while this is called.
+fn focus(element: &HtmlElement) {
+    let options = FocusOptions::new();
+    options.set_prevent_scroll(true);
+    let _ = element.focus_with_options(&options);
+}
+
+/// Returns the pre-edit text split into graphemes with their widths in cells, combining
+/// characters joined to the character before them.
+pub(crate) fn graphemes(text: &str) -> Vec<(String, u16)> {
+    let mut graphemes: Vec<(String, u16)> = Vec::new();
+    for c in text.chars() {
+        let width = c.width().unwrap_or(0) as u16;
+        match graphemes.last_mut() {
+            Some((grapheme, _)) if width == 0 => grapheme.push(c),
+            _ if width == 0 => {}
+            _ => graphemes.push((c.to_string(), width)),
+        }
+    }
+    graphemes
+}
+
+/// Reports committed text, a single character as a key press and anything longer as a paste.
+fn commit(screen: &Screen, text: String) {
+    let mut chars = text.chars();
+    let event = match (chars.next(), chars.next()) {
+        (None, _) => return,
+        (Some(c), None) => Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::empty())),
+        _ => Event::Paste(text),
+    };
+    screen.on_event.emit(event);
+}
+
+/// Adds the hidden text area next to the canvas of `screen` and listens to the text composed
+/// in it. Focusing the canvas moves the focus on to the text area.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let document = window().unwrap().document().unwrap();
+    let canvas = screen.borrow().canvas();
+    let input = document
+        .create_element("textarea")
+        .unwrap()
+        .dyn_into::<HtmlTextAreaElement>()
+        .unwrap();
+    for (name, value) in [
+        ("autocapitalize", "off"),
+        ("autocomplete", "off"),
+        ("autocorrect", "off"),
+        ("spellcheck", "false"),
+        ("aria-label", "Terminal input"),
+        // Tabbing reaches the canvas, which hands the focus on.
+        ("tabindex", "-1"),
+    ] {
+        let _ = input.set_attribute(name, value);
+    }
+    let style = input.style();
+    for (property, value) in [
+        ("position", "absolute"),
+        ("width", "1px"),
+        ("padding", "0"),
+        ("border", "0"),
+        ("margin", "0"),
+        ("opacity", "0"),
+        ("overflow", "hidden"),
+        ("resize", "none"),
+        ("white-space", "nowrap"),
+        ("caret-color", "transparent"),
+        ("pointer-events", "none"),
+    ] {
+        let _ = style.set_property(property, value);
+    }
+    if let Some(body) = document.body() {
+        let _ = body.append_child(&input);
+    }
+    screen.borrow_mut().ime.input = Some(input.clone());
+    if window().unwrap().document().unwrap().active_element() == Some(canvas.clone().into()) {
+        focus(&input);
+    }
+
+    // Set when the last key could not be identified, which virtual keyboards do for every key
+    // and only report the text they typed through `input` events.
+    let unidentified = Rc::new(Cell::new(false));
+
+    let forward = {
+        let input = input.clone();
+        Listener::new(&canvas, "focus", move |_: FocusEvent| focus(&input))
+    };
+
+    let keydown = {
+        let unidentified = Rc::clone(&unidentified);
+        Listener::new(&input, "keydown", move |event: KeyboardEvent| {
+            unidentified.set(
+                !event.is_composing() && matches!(event.key().as_str(), "Unidentified" | "Process"),
+            );
+        })
+    };
+
+    let start = {
+        let screen = Rc::clone(screen);
+        Listener::new(&input, "compositionstart", move |_: CompositionEvent| {
+            screen.borrow_mut().set_preedit(String::new());
+        })
+    };
+
+    let update = {
+        let screen = Rc::clone(screen);
+        Listener::new(
+            &input,
+            "compositionupdate",
+            move |event: CompositionEvent| {
+                screen
+                    .borrow_mut()
+                    .set_preedit(event.data().unwrap_or_default());
+            },
+        )
+    };
+
+    let end = {
+        let screen = Rc::clone(screen);
+        let area = input.clone();
+        Listener::new(&input, "compositionend", move |event: CompositionEvent| {
+            let mut screen = screen.borrow_mut();
+            screen.set_preedit(String::new());
+            commit(&screen, event.data().unwrap_or_default());
+            area.set_value("");
+        })
+    };
+
+    let typed = {
+        let screen = Rc::clone(screen);
+        let area = input.clone();
+        Listener::new(&input, "input", move |event: InputEvent| {
+            if event.is_composing() {
+                return;
+            }
+            // Identified keys have been reported as keys already.
+            if unidentified.replace(false) && event.input_type() == "insertText" {
+                commit(&screen.borrow(), event.data().unwrap_or_default());
+            }
+            area.set_value("");
+        })
+    };
+
+    vec![forward, keydown, start, update, end, typed]
+}
+
+/// Removes the hidden text area of `screen`, dropping any text being composed. If the text
+/// area has focus, the canvas takes it back.
+pub(crate) fn release(screen: &Rc<RefCell<Screen>>) {
+    let (input, canvas) = {
+        let mut screen = screen.borrow_mut();
+        screen.set_preedit(String::new());
+        (screen.ime.input.take(), screen.canvas())
+    };
+    let Some(input) = input else {
+        return;
+    };
+    let document = window().unwrap().document().unwrap();
+    if document.active_element() == Some(input.clone().into()) {
+        focus(&canvas);
+    }
+    input.remove();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_graphemes(text: &str, expected: &[(&str, u16)]) {
+        let expected: Vec<(String, u16)> = expected
+            .iter()
+            .map(|&(grapheme, width)| (grapheme.to_string(), width))
+            .collect();
+        assert_eq!(graphemes(text), expected);
+    }
+
+    #[test]
+    fn splits_text_into_cells() {
+        assert_graphemes("ab", &[("a", 1), ("b", 1)]);
+        assert_graphemes("", &[]);
+    }
+
+    #[test]
+    fn wide_characters_take_two_cells() {
+        assert_graphemes(
+            "\u{65e5}\u{672c}a",
+            &[("\u{65e5}", 2), ("\u{672c}", 2), ("a", 1)],
+        );
+    }
+
+    #[test]
+    fn combining_characters_join_the_one_before() {
+        assert_graphemes("e\u{301}x", &[("e\u{301}", 1), ("x", 1)]);
+        assert_graphemes("\u{3099}a", &[("a", 1)]);
+    }
+}
@@ -0,0 +1,238 @@
+//! Keyboard input, reported as key events.
+
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::{window, KeyboardEvent};
+
+use crate::{
+    clipboard,
+    dom::Listener,
+    event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    screen::Screen,
+};
+
+const CONTROL_SHIFT: KeyModifiers = KeyModifiers::CONTROL.union(KeyModifiers::SHIFT);
+const META_SHIFT: KeyModifiers = KeyModifiers::META.union(KeyModifiers::SHIFT);
+const META_ALT: KeyModifiers = KeyModifiers::META.union(KeyModifiers::ALT);
+
+/// The shortcuts passed through to the browser by default: opening, closing and switching
+/// tabs and windows, reloading, focusing the address bar, full screen and the developer tools.
+///
+/// The Command key equivalents of macOS are included. Alt combinations are left out, as
+/// terminal applications use them widely, e.g. Alt+D to delete a word.
+pub const DEFAULT_PASSTHROUGH: [KeyBinding; 28] = [
+    KeyBinding::new(KeyCode::Char('t'), KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::Char('t'), CONTROL_SHIFT),
+    KeyBinding::new(KeyCode::Char('n'), KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::Char('n'), CONTROL_SHIFT),
+    KeyBinding::new(KeyCode::Char('w'), KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::Char('w'), CONTROL_SHIFT),
+    KeyBinding::new(KeyCode::Tab, KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::BackTab, CONTROL_SHIFT),
+    KeyBinding::new(KeyCode::PageUp, KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::PageDown, KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::Char('r'), KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::Char('r'), CONTROL_SHIFT),
+    KeyBinding::new(KeyCode::F(5), KeyModifiers::empty()),
+    KeyBinding::new(KeyCode::F(5), KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::F(5), KeyModifiers::SHIFT),
+    KeyBinding::new(KeyCode::Char('l'), KeyModifiers::CONTROL),
+    KeyBinding::new(KeyCode::F(11), KeyModifiers::empty()),
+    KeyBinding::new(KeyCode::F(12), KeyModifiers::empty()),
+    KeyBinding::new(KeyCode::Char('i'), CONTROL_SHIFT),
+    KeyBinding::new(KeyCode::Char('j'), CONTROL_SHIFT),
+    KeyBinding::new(KeyCode::Char('t'), KeyModifiers::META),
+    KeyBinding::new(KeyCode::Char('t'), META_SHIFT),
+    KeyBinding::new(KeyCode::Char('n'), KeyModifiers::META),
+    KeyBinding::new(KeyCode::Char('w'), KeyModifiers::META),
+    KeyBinding::new(KeyCode::Char('r'), KeyModifiers::META),
+    KeyBinding::new(KeyCode::Char('l'), KeyModifiers::META),
+    KeyBinding::new(KeyCode::Char('q'), KeyModifiers::META),
+    KeyBinding::new(KeyCode::Char('i'), META_ALT),
+];
+
+/// The keys that scroll the page or move the focus, and `/` and `'`, which open the quick
+/// find bar of Firefox.
+const NAVIGATION_KEYS: [KeyCode; 13] = [
+    KeyCode::Tab,
+    KeyCode::BackTab,
+    KeyCode::Char(' '),
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::Char('/'),
+    KeyCode::Char('\''),
+];
+
+/// A key together with the modifiers held down for it, e.g. Ctrl+T.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    /// The key.
+    pub code: KeyCode,
+    /// The modifier keys held down, all of which have to match.
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Creates the binding of `code` with `modifiers`.
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyBinding {
+        KeyBinding { code, modifiers }
+    }
+
+    /// Returns `true` if `key` is this binding.
+    ///
+    /// Letters match in either case, so Ctrl+T with Caps Lock on is still Ctrl+T, while
+    /// Shift has to match like any other modifier.
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let code = match (self.code, key.code) {
+            (KeyCode::Char(a), KeyCode::Char(b)) => a.eq_ignore_ascii_case(&b),
+            (a, b) => a == b,
+        };
+        code && self.modifiers == key.modifiers
+    }
+}
+
+/// Which keys reported to the application have the browser's default action suppressed, see
+/// [`CanvasBackend::set_prevent_default`](crate::CanvasBackend::set_prevent_default).
+///
+/// Keys [passed through](crate::CanvasBackend::set_key_passthrough) to the browser are never
+/// suppressed, as they are not reported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PreventDefault {
+    /// No key, so e.g. Space also scrolls the page and Tab moves the focus off the canvas.
+    Never,
+    /// Keys the browser acts on: Tab, Shift+Tab, Space, the arrows, Page Up / Page Down and
+    /// Home / End, which scroll the page or move the focus, `/` and `'`, which open the quick
+    /// find bar of Firefox, and shortcuts held with Ctrl, Alt or Meta. Text typed without
+    /// them is left alone.
+    #[default]
+    BrowserKeys,
+    /// The given keys, with any modifiers.
+    Keys(Vec<KeyCode>),
+    /// Every key, including browser shortcuts the browser lets pages override.
+    All,
+}
+
+impl PreventDefault {
+    /// Returns `true` if the default action of `key` is suppressed.
+    pub fn prevents(&self, key: &KeyEvent) -> bool {
+        match self {
+            PreventDefault::Never => false,
+            PreventDefault::BrowserKeys => {
+                NAVIGATION_KEYS.contains(&key.code)
+                    || key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::META)
+            }
+            PreventDefault::Keys(keys) => keys.contains(&key.code),
+            PreventDefault::All => true,
+        }
+    }
+}
+
+/// Reports key presses, repeats and releases while the canvas has focus and the application
+/// takes input, suppressing the browser's default action for them as the policy of `screen`
+/// says.
+///
+/// Keys passed through to the browser are left out, and so are keys taken by an IME and
+/// paste shortcuts, as the text they enter is reported instead, and copy shortcuts while
+/// text is selected.
+///
+/// The listeners are on the window, which events reach after the document, so keys the
+/// backend handles itself, e.g. Shift+PageUp, have already been marked as handled and are not
+/// reported.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let window = window().unwrap();
+    ["keydown", "keyup"]
+        .into_iter()
+        .map(|kind| {
+            let screen = Rc::clone(screen);
+            Listener::active(&window, kind, move |event: KeyboardEvent| {
+                if event.default_prevented()
+                    || event.is_composing()
+                    || clipboard::is_paste_key(&event)
+                {
+                    return;
+                }
+                let screen = screen.borrow();
+                if !screen.capture || !screen.has_focus() {
+                    return;
+                }
+                if screen.selection.is_some() && clipboard::is_copy_key(&event) {
+                    return;
+                }
+                let Some(key) = KeyEvent::from_keyboard_event(&event) else {
+                    return;
+                };
+                if screen
+                    .passthrough
+                    .iter()
+                    .any(|binding| binding.matches(&key))
+                {
+                    return;
+                }
+                if screen.prevent_default.prevents(&key) {
+                    event.prevent_default();
+                }
+                screen.on_event.emit(Event::Key(key));
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::KeyEventKind;
+
+    #[test]
+    fn bindings_match_letters_in_either_case() {
+        let binding = KeyBinding::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        assert!(binding.matches(&KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL)));
+        assert!(binding.matches(&KeyEvent::new(KeyCode::Char('T'), KeyModifiers::CONTROL)));
+        assert!(!binding.matches(&KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn bindings_match_modifiers_exactly() {
+        let binding = KeyBinding::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        assert!(!binding.matches(&KeyEvent::new(KeyCode::Char('T'), CONTROL_SHIFT)));
+        assert!(!binding.matches(&KeyEvent::new(KeyCode::Char('t'), KeyModifiers::empty())));
+        assert!(!binding.matches(&KeyEvent::new(
+            KeyCode::Char('t'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT
+        )));
+    }
+
+    #[test]
+    fn bindings_match_other_keys_and_every_kind() {
+        let binding = KeyBinding::new(KeyCode::F(5), KeyModifiers::empty());
+        assert!(binding.matches(&KeyEvent::new(KeyCode::F(5), KeyModifiers::empty())));
+        assert!(binding.matches(&KeyEvent::new_with_kind(
+            KeyCode::F(5),
+            KeyModifiers::empty(),
+            KeyEventKind::Release
+        )));
+        assert!(!binding.matches(&KeyEvent::new(KeyCode::F(6), KeyModifiers::empty())));
+    }
+
+    #[test]
+    fn default_passthrough_keeps_browser_shortcuts() {
+        let passes = |code, modifiers| {
+            let key = KeyEvent::new(code, modifiers);
+            DEFAULT_PASSTHROUGH
+                .iter()
+                .any(|binding| binding.matches(&key))
+        };
+        assert!(passes(KeyCode::Char('t'), KeyModifiers::CONTROL));
+        assert!(passes(KeyCode::Char('I'), META_ALT));
+        assert!(passes(KeyCode::F(5), KeyModifiers::empty()));
+        assert!(!passes(KeyCode::Char('d'), KeyModifiers::ALT));
+        assert!(!passes(KeyCode::Char('c'), KeyModifiers::CONTROL));
+    }
+}
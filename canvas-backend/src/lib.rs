@@ -1,195 +1,644 @@
 //! This module provides the [`CanvasBackend`] implementation for the [`Backend`] trait.
 
-use std::io::{self, Write};
+use std::{
+    cell::RefCell,
+    future::Future,
+    io::{self, Write},
+    rc::Rc,
+};
 
-use wasm_bindgen::JsValue;
-use web_sys::{console, window, CanvasRenderingContext2d};
+use web_sys::{console, CanvasRenderingContext2d};
 
 use ratatui::{
     backend::{Backend, ClearType, WindowSize},
     buffer::Cell,
     layout::Size,
     prelude::Rect,
-    style::{Color, Modifier},
+    style::Modifier,
 };
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+pub use bell::BELL_INTERVAL;
+pub use cursor::CursorShape;
+pub use font::{Font, FontSource};
+pub use image::{ImageFit, ImagePlacement, ImageSource};
+pub use keyboard::{KeyBinding, PreventDefault, DEFAULT_PASSTHROUGH};
+pub use link::{LinkArea, LinkId, DEFAULT_LINK_SCHEMES};
+pub use metrics::{CellGeometry, CellMetrics};
+pub use scrollback::DEFAULT_SCROLLBACK;
+pub use theme::Theme;
+pub use zoom::{MAX_FONT_SIZE, MIN_FONT_SIZE};
+
+mod bell;
+mod blink;
+mod clipboard;
+#[cfg(feature = "crossterm")]
+pub mod crossterm;
+mod cursor;
+mod dom;
+pub mod event;
+mod focus;
+mod font;
+mod glyph;
+mod image;
+mod ime;
+mod keyboard;
+mod link;
+mod metrics;
+mod mouse;
+mod page;
+mod screen;
+mod scrollback;
+mod selection;
+mod theme;
+mod touch;
+mod vt;
+mod zoom;
+
+use dom::{Interval, Listener};
+use event::{Event, EventStream};
+use screen::Screen;
+use vt::Vt;
+
+#[derive(Debug)]
 pub struct CanvasBackend<W: Write> {
-    context: CanvasRenderingContext2d,
+    screen: Rc<RefCell<Screen>>,
     writer: W,
+    zoom: Vec<Listener>,
+    mouse: Vec<Listener>,
+    touch: Vec<Listener>,
+    ime: Vec<Listener>,
+    _keyboard: Vec<Listener>,
+    _clipboard: Listener,
+    _selection: Vec<Listener>,
+    _focus: Vec<Listener>,
+    _scrollback: Vec<Listener>,
+    _links: Vec<Listener>,
+    _page: Vec<Listener>,
+    _reduced_motion: Option<Listener>,
+    _blink: Interval,
+    cursor_blink: Option<Interval>,
+    vt: Option<Vt>,
 }
 
 impl<W> CanvasBackend<W>
 where
     W: Write,
 {
+    /// Creates a backend drawing with `context`.
+    ///
+    /// The canvas should be sized with CSS. Its backing store is kept at that size in device
+    /// pixels, so the grid fills the canvas and stays crisp at any device pixel ratio.
     pub fn new(context: CanvasRenderingContext2d, writer: W) -> CanvasBackend<W> {
-        let canvas = context.canvas().unwrap();
-        let rect = canvas.get_bounding_client_rect();
+        let screen = Rc::new(RefCell::new(Screen::new(context)));
 
-        canvas.set_width((rect.width()) as u32);
-        canvas.set_height((rect.height()) as u32);
+        // Fonts used by the page may still be in flight, so hold off painting until they
+        // arrive rather than flashing a fallback font.
+        if font::loading() {
+            font::wait(&screen, None);
+        }
 
-        CanvasBackend { context, writer }
+        let keyboard = keyboard::listen(&screen);
+        let touch = touch::listen(&screen);
+        let clipboard = clipboard::listen(&screen);
+        let selection = selection::listen(&screen);
+        let focus = focus::listen(&screen);
+        let scrollback = scrollback::listen(&screen);
+        let links = link::listen(&screen);
+        let page = page::listen(&screen);
+        let reduced_motion = blink::listen_reduced_motion(&screen);
+        let blink = blink::animate(&screen);
+
+        CanvasBackend {
+            screen,
+            writer,
+            zoom: Vec::new(),
+            mouse: Vec::new(),
+            touch,
+            ime: Vec::new(),
+            _keyboard: keyboard,
+            _clipboard: clipboard,
+            _selection: selection,
+            _focus: focus,
+            _scrollback: scrollback,
+            _links: links,
+            _page: page,
+            _reduced_motion: reduced_motion,
+            _blink: blink,
+            cursor_blink: None,
+            vt: None,
+        }
     }
-}
 
-impl<W> Write for CanvasBackend<W>
-where
-    W: Write,
-{
-    /// Writes a buffer of bytes to the underlying buffer.
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        self.writer.write(buf)
+    /// Registers a web font named `family` and renders cells with it.
+    ///
+    /// The font is loaded through the `FontFace` API and added to `document.fonts`. Until it
+    /// has loaded, cells are only recorded; once it has, the cells are re-measured and the whole
+    /// canvas is repainted. The previous font family is kept as a fallback in case loading
+    /// fails.
+    pub fn load_font(&mut self, family: &str, source: FontSource) {
+        self.screen.borrow_mut().font.set_family(family);
+        font::load(&self.screen, family, &source);
     }
 
-    /// Flushes the underlying buffer.
-    fn flush(&mut self) -> io::Result<()> {
-        self.writer.flush()
+    /// Returns a future that resolves once `document.fonts` has finished loading.
+    ///
+    /// Awaiting it before creating the [`Terminal`](ratatui::Terminal) makes the first layout
+    /// use the metrics of the final font.
+    pub fn fonts_ready(&self) -> impl Future<Output = ()> + 'static {
+        let screen = Rc::clone(&self.screen);
+        async move {
+            if let Err(err) = font::ready().await {
+                console::error_1(&err);
+            }
+            screen.borrow_mut().remeasure();
+        }
     }
-}
 
-impl<W> Backend for CanvasBackend<W>
-where
-    W: Write,
-{
-    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    /// Returns the font cells are rendered with.
+    pub fn font(&self) -> Font {
+        self.screen.borrow().font.clone()
+    }
+
+    /// Renders cells with `font`, re-measuring and repainting the canvas.
+    ///
+    /// Use this to configure the fallback families glyphs missing from the primary family are
+    /// taken from.
+    pub fn set_font(&mut self, font: Font) {
+        let mut screen = self.screen.borrow_mut();
+        screen.font = font;
+        screen.remeasure();
+    }
+
+    /// Renders cells at `size` CSS pixels, clamped to [`MIN_FONT_SIZE`] and [`MAX_FONT_SIZE`].
+    ///
+    /// The cells are re-measured and, if the number of cells that fit into the canvas changes,
+    /// the callback registered with [`on_resize`](Self::on_resize) is called so the
+    /// application can draw again at the new size.
+    pub fn set_font_size(&mut self, size: f64) {
+        self.screen.borrow_mut().set_font_size(size);
+    }
+
+    /// Zooms the font with Ctrl+= / Ctrl+- / Ctrl+0 and Ctrl+wheel like a desktop terminal.
+    ///
+    /// The browser's own page zoom is prevented for these inputs while enabled.
+    pub fn set_zoom_keys(&mut self, enabled: bool) {
+        self.zoom = if enabled {
+            zoom::listen(&self.screen)
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Registers a callback called with the new size whenever the number of cells changes,
+    /// e.g. after zooming.
+    ///
+    /// The callback runs after the current task, so it may borrow the `Terminal` owning this
+    /// backend and draw.
+    pub fn on_resize(&mut self, callback: impl FnMut(Rect) + 'static) {
+        self.screen.borrow().on_resize.set(callback);
+    }
+
+    /// Registers a callback called with every input event: key presses and releases, mouse
+    /// events while [captured](Self::set_mouse_capture), pasted text, the canvas gaining and
+    /// losing focus and changes of the grid size.
+    ///
+    /// Registering makes the canvas focusable with a `tabindex` and focuses it, unless another
+    /// element on the page has focus. Keys and pasted text are only reported while it has
+    /// focus, so typing into other elements on the page does not reach the application. Until
+    /// then, the backend leaves the focus and the keys to the page.
+    ///
+    /// The callback runs after the current task, so it may borrow the `Terminal` owning this
+    /// backend and draw.
+    pub fn on_event(&mut self, callback: impl FnMut(Event) + 'static) {
+        self.screen.borrow().on_event.set(callback);
+        focus::capture(&self.screen);
+    }
+
+    /// Returns a stream of the input events [`on_event`](Self::on_event) reports, from now on,
+    /// for an application that awaits events rather than registering a callback.
+    ///
+    /// Like registering a callback, this starts taking focus and keys.
+    pub fn event_stream(&self) -> EventStream {
+        let stream = self.screen.borrow().on_event.stream();
+        focus::capture(&self.screen);
+        stream
+    }
+
+    /// Sets which keys reported to the application have the browser's default action
+    /// suppressed, [`PreventDefault::BrowserKeys`] by default, so e.g. Space and the arrows do
+    /// not scroll the page, Tab does not move the focus off the canvas and Ctrl+F reaches the
+    /// application rather than opening the find bar.
+    ///
+    /// Keys are only reported, and so suppressed, once the application has registered for
+    /// events with [`on_event`](Self::on_event) or [`event_stream`](Self::event_stream).
+    pub fn set_prevent_default(&mut self, policy: PreventDefault) {
+        self.screen.borrow_mut().prevent_default = policy;
+    }
+
+    /// Passes the key `bindings` through to the browser, [`DEFAULT_PASSTHROUGH`] by default,
+    /// so e.g. Ctrl+T still opens a tab and F5 reloads the page. Those keys are not reported
+    /// to the application, while every other key is, with the browser's default action
+    /// suppressed as [`set_prevent_default`](Self::set_prevent_default) says.
+    ///
+    /// Some shortcuts, e.g. Ctrl+T, Ctrl+W and Ctrl+N in Chrome, never reach the page outside
+    /// of full screen and installed web apps, so taking them off the list only makes a
+    /// difference there.
+    pub fn set_key_passthrough<I>(&mut self, bindings: I)
     where
-        I: Iterator<Item = (u16, u16, &'a Cell)>,
+        I: IntoIterator<Item = KeyBinding>,
     {
-        let mut fg = Color::Reset;
-        let mut bg = Color::Reset;
-        let mut underline_color = Color::Reset;
-        let mut modifier = Modifier::empty();
-        let mut last_pos: Option<(u16, u16)> = None;
-
-        self.context.set_fill_style(&JsValue::from("#263238"));
-        let canvas = self.context.canvas().unwrap();
-        let rect = canvas.get_bounding_client_rect();
-        self.context
-            .fill_rect(0.0, 0.0, rect.width(), rect.height());
+        self.screen.borrow_mut().passthrough = bindings.into_iter().collect();
+    }
 
-        for (x, y, cell) in content {
-            // self.context.close_path()
-            // Move the cursor if the previous location was not (x - 1, y)
-            if !matches!(last_pos, Some(p) if x == p.0 + 1 && y == p.1) {
-                self.context.move_to(x as f64, y.saturating_add(10) as f64)
-            }
+    /// Reports mouse events over the grid to the callback registered with
+    /// [`on_event`](Self::on_event), like enabling mouse capture in a terminal does.
+    ///
+    /// Pixel positions are mapped to the cell under the pointer with the current
+    /// [metrics](Self::metrics). While a button is held down the pointer is captured, so a drag
+    /// keeps being reported at the nearest cell when it leaves the canvas. While enabled, the
+    /// wheel scrolls the application rather than the scrollback, and the right button no
+    /// longer opens the context menu. Text is still selected with Shift held down, and those
+    /// presses are not reported.
+    pub fn set_mouse_capture(&mut self, enabled: bool) {
+        self.screen.borrow_mut().mouse_capture = enabled;
+        self.mouse = if enabled {
+            mouse::listen(&self.screen)
+        } else {
+            Vec::new()
+        };
+    }
 
-            let canvas = self.context.canvas().unwrap();
-            let rect = canvas.get_bounding_client_rect();
-            let res = window().unwrap().device_pixel_ratio();
-            // console::log_1(&format!("symbol={}, x={x}, y={y}, res={res}", cell.symbol).into());
-
-            last_pos = Some((x, y));
-
-            let ch = 16.0;
-            let cw = 16.0;
-            let lh = 18.0;
-            let descent = (lh - ch) / 2.0;
-
-            let x = x as f64 * cw;
-            let y = y as f64 * lh + 5.0;
-            let yi = (y + lh - descent).floor();
-
-            if cell.modifier != modifier {
-                // let diff = ModifierDiff {
-                //     from: modifier,
-                //     to: cell.modifier,
-                // };
-                // diff.queue(&mut self.writer)?;
-                // modifier = cell.modifier;
-            }
-            if cell.bg != bg {
-                // let color = CColor::from(cell.bg);
-                // queue!(self.writer, SetBackgroundColor(color))?;
-                // console::log_1(&format!("color={}", cell.bg).into());
-                // console::log_1(&format!("init={:?}", self.context.fill_style().as_string()).into());
+    /// Translates touches on the canvas into terminal input, which is on by default.
+    ///
+    /// A tap clicks and a long-press right-clicks while the mouse is
+    /// [captured](Self::set_mouse_capture). A vertical swipe scrolls like the wheel, keeping
+    /// on with inertia once the finger lifts, and a pinch zooms the font. Cells are hit
+    /// tested like for the mouse. While enabled, the browser does not pan or zoom the canvas.
+    pub fn set_touch_input(&mut self, enabled: bool) {
+        self.touch = if enabled {
+            touch::listen(&self.screen)
+        } else {
+            touch::release(&self.screen.borrow().canvas());
+            Vec::new()
+        };
+    }
+
+    /// Takes text input through a hidden text area that follows the cursor, so input method
+    /// editors for e.g. Chinese, Japanese and Korean and virtual keyboards can type into the
+    /// application.
+    ///
+    /// Focusing the canvas moves the focus on to the text area. The text being composed is
+    /// drawn at the cursor, underlined, and the committed text is reported to the callback
+    /// registered with [`on_event`](Self::on_event), a single character as a key press and
+    /// anything longer as a paste.
+    pub fn set_ime(&mut self, enabled: bool) {
+        self.ime = Vec::new();
+        ime::release(&self.screen);
+        if enabled {
+            self.ime = ime::listen(&self.screen);
+            self.screen.borrow().place_ime();
+        }
+    }
 
-                self.context
-                    .set_fill_style(&JsValue::from(cell.bg.to_string()));
-                self.context.fill_rect(x, y, 16.0, 18.0);
+    /// Shapes cells according to `geometry`, e.g. [`CellGeometry::AspectRatio(1.0)`] for
+    /// square cells.
+    ///
+    /// Glyphs are centred in cells larger than them, and [`size`](Backend::size) reports the
+    /// grid of cells of the new shape.
+    ///
+    /// [`CellGeometry::AspectRatio(1.0)`]: CellGeometry::AspectRatio
+    pub fn set_cell_geometry(&mut self, geometry: CellGeometry) {
+        let mut screen = self.screen.borrow_mut();
+        screen.geometry = geometry;
+        screen.remeasure();
+    }
 
-                self.context.set_fill_style(&JsValue::from("#000000"));
+    /// Sets the colours cells fall back to and repaints the canvas.
+    pub fn set_theme(&mut self, theme: Theme) {
+        let mut screen = self.screen.borrow_mut();
+        screen.theme = theme;
+        screen.repaint();
+    }
 
-                // bg = cell.bg;
-            }
-            // if cell.underline_color != underline_color {
-            //     // let color = CColor::from(cell.underline_color);
-            //     // queue!(self.writer, SetUnderlineColor(color))?;
-            //     // underline_color = cell.underline_color;
-            // }
+    /// Returns the colours cells fall back to.
+    pub fn theme(&self) -> Theme {
+        self.screen.borrow().theme
+    }
+
+    /// Draws the cursor as a block, an underline or a bar.
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        self.screen
+            .borrow_mut()
+            .update_cursor(|cursor| cursor.shape = shape);
+    }
+
+    /// Makes the cursor blink while the canvas has focus.
+    pub fn set_cursor_blink(&mut self, blink: bool) {
+        self.cursor_blink = blink.then(|| cursor::blink(&self.screen));
+        self.screen.borrow_mut().update_cursor(|cursor| {
+            cursor.blink = blink;
+            cursor.blinked_off = false;
+        });
+    }
 
-            // queue!(self.writer, Print(&cell.symbol))?;
+    /// Keeps at most `rows` rows scrolled off the top of the screen, [`DEFAULT_SCROLLBACK`] by
+    /// default. `0` disables the scrollback.
+    ///
+    /// Rows end up in the scrollback when [`append_lines`](Backend::append_lines) scrolls the
+    /// screen, e.g. through `Terminal::insert_before`. The mouse wheel and Shift+PageUp /
+    /// Shift+PageDown browse through them, and any new output returns to the live screen.
+    pub fn set_scrollback_limit(&mut self, rows: usize) {
+        let mut screen = self.screen.borrow_mut();
+        screen.scrollback.set_limit(rows);
+        screen.repaint();
+    }
 
-            // console::log_1(&format!("after symbol={}, x={x}, y={y}", cell.symbol).into());
+    /// Scrolls the view `rows` rows back into the scrollback, or forward towards the live
+    /// screen if negative.
+    pub fn scroll_history(&mut self, rows: isize) {
+        self.screen.borrow_mut().scroll_view(rows);
+    }
 
-            // console::log_1(&format!("symbol={}, x={x}, y={y}", cell.symbol).into());
-            // let width = rect.width() as u16;
-            // let height = rect.height() as u16;
+    /// Returns how many rows the view is scrolled back from the live screen.
+    pub fn history_offset(&self) -> usize {
+        self.screen.borrow().scrollback.offset
+    }
 
-            // if *chars.first().unwrap() == ' ' {
-            //     continue;
-            // }
+    /// Makes the cells of `area` a link to `url`, opened in a new tab when clicked.
+    ///
+    /// The link stays on those cells until removed, whatever is drawn on them. Fails with
+    /// [`io::ErrorKind::InvalidInput`] if `url` uses a scheme not allowed by
+    /// [`set_link_schemes`](Self::set_link_schemes).
+    pub fn add_link(&mut self, area: LinkArea, url: &str) -> io::Result<LinkId> {
+        let mut id = None;
+        self.screen
+            .borrow_mut()
+            .update_links(|links| id = links.add(area, url));
+        id.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("URL scheme not allowed: {url}"),
+            )
+        })
+    }
 
-            // console::log_1(&format!("font height={}", ch.floor()).into());
+    /// Removes the link `id`.
+    pub fn remove_link(&mut self, id: LinkId) {
+        self.screen.borrow_mut().update_links(|links| {
+            links.remove(id);
+        });
+    }
 
-            self.context
-                .set_font(&format!("{}px monospace", ch.floor()));
+    /// Removes every link added with [`add_link`](Self::add_link).
+    pub fn clear_links(&mut self) {
+        self.screen.borrow_mut().update_links(|links| links.clear());
+    }
 
-            self.context
-                .set_fill_style(&JsValue::from(Color::White.to_string()));
-            if cell.fg != fg {
-                // let color = CColor::from(cell.fg);
-                // queue!(self.writer, SetForegroundColor(color))?;
-                self.context
-                    .set_fill_style(&JsValue::from(cell.fg.to_string()));
+    /// Turns URLs in the drawn text into links, for the schemes allowed by
+    /// [`set_link_schemes`](Self::set_link_schemes).
+    ///
+    /// A URL is detected within a single row, and trailing punctuation is left out of it.
+    pub fn set_link_detection(&mut self, enabled: bool) {
+        let mut screen = self.screen.borrow_mut();
+        screen.links.set_detect(enabled);
+        screen.detect_links();
+    }
 
-                // fg = cell.fg;
+    /// Allows links to use only the URL `schemes`, [`DEFAULT_LINK_SCHEMES`] by default.
+    ///
+    /// Links with other schemes are neither added nor opened, so `javascript:` URLs never run.
+    /// Links already added with another scheme are removed.
+    pub fn set_link_schemes<I, S>(&mut self, schemes: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let schemes = schemes.into_iter().map(Into::into).collect();
+        let mut screen = self.screen.borrow_mut();
+        screen.links.set_schemes(schemes);
+        screen.detect_links();
+    }
+
+    /// Shows `source` over the cells of `area`, scaled according to `fit`.
+    ///
+    /// The image is composited over the cells whenever they are painted, so it survives
+    /// redraws and repaints. It is removed when the returned placement is dropped or once any
+    /// cell under it is overwritten or cleared; mark those cells with
+    /// [`Cell::set_skip`](ratatui::buffer::Cell::set_skip) or leave them unchanged between
+    /// frames to keep it. Fails with [`io::ErrorKind::InvalidInput`] if RGBA data does not
+    /// match its size.
+    pub fn place_image(
+        &mut self,
+        area: Rect,
+        source: ImageSource,
+        fit: ImageFit,
+    ) -> io::Result<ImagePlacement> {
+        image::place(&self.screen, area, source, fit)
+    }
+
+    /// Rings the bell, like writing `\x07` to the backend does.
+    ///
+    /// The bell flashes the canvas, beeps if enabled with
+    /// [`set_audible_bell`](Self::set_audible_bell) and calls the callback registered with
+    /// [`on_bell`](Self::on_bell). Bells rung within [`BELL_INTERVAL`] milliseconds of the last
+    /// one are dropped.
+    pub fn bell(&mut self) {
+        self.screen.borrow_mut().ring_bell();
+    }
+
+    /// Flashes the canvas when the bell rings, which is on by default.
+    ///
+    /// The canvas never flashes while the user prefers reduced motion.
+    pub fn set_visual_bell(&mut self, enabled: bool) {
+        self.screen.borrow_mut().bell.visual = enabled;
+    }
+
+    /// Plays a short beep through WebAudio when the bell rings, which is off by default.
+    ///
+    /// Browsers only allow audio once the user has interacted with the page, so bells before
+    /// that are silent.
+    pub fn set_audible_bell(&mut self, enabled: bool) {
+        self.screen.borrow_mut().bell.audible = enabled;
+    }
+
+    /// Registers a callback called whenever the bell rings.
+    ///
+    /// The callback runs after the current task, so it may borrow the `Terminal` owning this
+    /// backend.
+    pub fn on_bell(&mut self, mut callback: impl FnMut() + 'static) {
+        self.screen.borrow().bell.on_bell.set(move |()| callback());
+    }
+
+    /// Writes `text` to the system clipboard, resolving once it has been written.
+    ///
+    /// This uses `navigator.clipboard` where available. In insecure contexts, or if the
+    /// browser denies access, it falls back to `document.execCommand("copy")`, which only
+    /// works in response to user input, e.g. a key press. Fails with [`io::ErrorKind::Other`]
+    /// if neither works.
+    pub fn write_clipboard(
+        &mut self,
+        text: &str,
+    ) -> impl Future<Output = io::Result<()>> + 'static {
+        clipboard::write(text)
+    }
+
+    /// Returns the selected text, if any, rebuilt from the cells as plain text with one line per
+    /// row and trailing spaces trimmed.
+    ///
+    /// Text is selected with the mouse: a drag selects from line to line, an Alt-drag selects
+    /// a rectangle, a double-click a word and a triple-click a line. While the mouse is
+    /// [captured](Self::set_mouse_capture), Shift has to be held down to select. The browser's
+    /// copy action, e.g. Ctrl+C, copies the selection and clears it, and the copy shortcut is
+    /// not reported as a key while text is selected.
+    pub fn selected_text(&self) -> Option<String> {
+        self.screen.borrow().selected_text()
+    }
+
+    /// Writes the selected text to the system clipboard like
+    /// [`write_clipboard`](Self::write_clipboard), doing nothing if no text is selected.
+    pub fn copy_selection(&mut self) -> impl Future<Output = io::Result<()>> + 'static {
+        let text = self.selected_text();
+        let written = text.as_deref().map(clipboard::write);
+        async move {
+            match written {
+                Some(written) => written.await,
+                None => Ok(()),
             }
-            self.context.fill_text(&cell.symbol, x, yi).unwrap();
+        }
+    }
+
+    /// Clears the selection.
+    pub fn clear_selection(&mut self) {
+        self.screen.borrow_mut().select(None);
+    }
+
+    /// Sets the title of the page, shown on its tab like a terminal shows it on its window.
+    ///
+    /// With the [VT parser](Self::set_vt_parser) enabled, `OSC 0` and `OSC 2` sequences set
+    /// it too.
+    pub fn set_title(&mut self, title: &str) {
+        page::set_title(title);
+    }
+
+    /// Shows the canvas fullscreen, or leaves fullscreen.
+    ///
+    /// The grid is fitted to the new canvas size once the switch is done, and the callback
+    /// registered with [`on_resize`](Self::on_resize) is called with it. Browsers only allow
+    /// entering fullscreen in response to user input, e.g. a key press.
+    pub fn set_fullscreen(&mut self, fullscreen: bool) -> io::Result<()> {
+        page::set_fullscreen(&self.screen.borrow().canvas(), fullscreen)
+    }
+
+    /// Enters fullscreen if the canvas is not shown fullscreen, and leaves it otherwise.
+    pub fn toggle_fullscreen(&mut self) -> io::Result<()> {
+        let fullscreen = self.is_fullscreen();
+        self.set_fullscreen(!fullscreen)
+    }
+
+    /// Returns `true` if the canvas is shown fullscreen.
+    pub fn is_fullscreen(&self) -> bool {
+        page::is_fullscreen(&self.screen.borrow().canvas())
+    }
+
+    /// Puts a dot on the favicon when new output is drawn while the tab is in the background,
+    /// until the tab is visible again.
+    pub fn set_activity_badge(&mut self, enabled: bool) {
+        self.screen.borrow_mut().badge.set_enabled(enabled);
+    }
+
+    /// Interprets bytes written to the backend as VT100/xterm output, so raw ANSI text such as
+    /// captured CLI logs can be shown next to ratatui widgets.
+    ///
+    /// SGR colours and attributes, cursor movement, erasing, inserting and deleting, scroll
+    /// regions and the alternate screen are supported. The bytes are still passed on to the
+    /// inner writer.
+    ///
+    /// A line feed returns to the first column as well, so plain `\n`-separated logs line up,
+    /// as there is no line discipline turning `\n` into `\r\n` like in a terminal. Write
+    /// `\x1b[20l` to make it only move down a row.
+    pub fn set_vt_parser(&mut self, enabled: bool) {
+        self.vt = enabled.then(Vt::default);
+    }
+
+    /// Returns the current cell metrics.
+    pub fn metrics(&self) -> CellMetrics {
+        self.screen.borrow().metrics
+    }
+}
 
-            if cell.fg != fg {
-                self.context.set_fill_style(&JsValue::from("#000000"));
+impl<W> Write for CanvasBackend<W>
+where
+    W: Write,
+{
+    /// Writes a buffer of bytes to the underlying buffer, first drawing them on the canvas if
+    /// the [VT parser](Self::set_vt_parser) is enabled.
+    ///
+    /// A `BEL` byte rings the [bell](Self::bell).
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match &mut self.vt {
+            Some(vt) => {
+                vt.feed(&mut self.screen.borrow_mut(), buf);
+                self.writer.write_all(buf)?;
+                Ok(buf.len())
+            }
+            None => {
+                let written = self.writer.write(buf)?;
+                if buf[..written].contains(&0x07) {
+                    self.screen.borrow_mut().ring_bell();
+                }
+                Ok(written)
             }
         }
+    }
+
+    /// Flushes the underlying buffer.
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
 
-        // #[cfg(feature = "underline-color")]
-        // return queue!(
-        //     self.writer,
-        //     SetForegroundColor(CColor::Reset),
-        //     SetBackgroundColor(CColor::Reset),
-        //     SetUnderlineColor(CColor::Reset),
-        //     SetAttribute(CAttribute::Reset),
-        // );
-        // #[cfg(not(feature = "underline-color"))]
-        // return queue!(
-        //     self.writer,
-        //     SetForegroundColor(CColor::Reset),
-        //     SetBackgroundColor(CColor::Reset),
-        //     SetAttribute(CAttribute::Reset),
-        // );
+impl<W> Backend for CanvasBackend<W>
+where
+    W: Write,
+{
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        let mut screen = self.screen.borrow_mut();
+        for (x, y, cell) in content {
+            screen.set_cell(x, y, cell);
+        }
+        screen.detect_links();
+        screen.badge.activity();
+        screen.paint_cursor();
         Ok(())
     }
 
     fn hide_cursor(&mut self) -> io::Result<()> {
-        // execute!(self.writer, Hide)
+        self.screen
+            .borrow_mut()
+            .update_cursor(|cursor| cursor.visible = false);
         Ok(())
     }
 
     fn show_cursor(&mut self) -> io::Result<()> {
-        // execute!(self.writer, Show)
+        self.screen
+            .borrow_mut()
+            .update_cursor(|cursor| cursor.visible = true);
         Ok(())
     }
 
     fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
-        // crossterm::cursor::position()
-        //     .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))
-        Ok((0, 0))
+        let cursor = self.screen.borrow().cursor;
+        Ok((cursor.x, cursor.y))
     }
 
     fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
-        self.context.move_to(x as f64, y as f64);
+        self.screen.borrow_mut().update_cursor(|cursor| {
+            cursor.x = x;
+            cursor.y = y;
+        });
         Ok(())
     }
 
@@ -197,54 +646,52 @@ where
         self.clear_region(ClearType::All)
     }
 
+    /// Clears the cells of `clear_type` relative to the cursor, both ends inclusive, and fills
+    /// them with the theme background.
     fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
-        // self.writer.flush().unwrap();
-
-        let rect = self.context.canvas().unwrap().get_bounding_client_rect();
-        self.context
-            .clear_rect(0.0, 0.0, rect.width(), rect.height());
-
+        let mut screen = self.screen.borrow_mut();
+        let (x, y) = (screen.cursor.x, screen.cursor.y);
+        let area = screen.buffer.area;
+        let (right, bottom) = (area.width.saturating_sub(1), area.height.saturating_sub(1));
+
+        match clear_type {
+            ClearType::All => screen.clear((0, 0), (right, bottom)),
+            ClearType::AfterCursor => screen.clear((x, y), (right, bottom)),
+            ClearType::BeforeCursor => screen.clear((0, 0), (x, y)),
+            ClearType::CurrentLine => screen.clear((0, y), (right, y)),
+            ClearType::UntilNewLine => screen.clear((x, y), (right, y)),
+        }
         Ok(())
     }
 
+    /// Moves the cursor down `n` rows, scrolling the content up and clearing the rows that come
+    /// in once the cursor reaches the bottom, like printing `n` line feeds in a terminal.
+    ///
+    /// This is what `Viewport::Inline` and `Terminal::insert_before` rely on to push output
+    /// above the viewport.
     fn append_lines(&mut self, n: u16) -> io::Result<()> {
-        for _ in 0..n {
-            self.context.fill_text("lineeeeeeeeee", 0.0, 0.0).unwrap();
-            // self.context.
-        }
+        self.screen.borrow_mut().line_feed(n);
         self.writer.flush()
     }
 
     fn size(&self) -> io::Result<Rect> {
-        let canvas = self.context.canvas().unwrap();
-        let rect = canvas.get_bounding_client_rect();
-        let res = window().unwrap().device_pixel_ratio();
-        let width = (rect.width() / 16.0) as u16;
-        let height = (rect.height() / 18.0) as u16;
-
-        canvas.set_width((rect.width()) as u32);
-        canvas.set_height((rect.height()) as u32);
-
-        console::log_1(&format!("width={width}, height={height}").into());
-
-        Ok(Rect::new(0, 0, width, height))
+        let mut screen = self.screen.borrow_mut();
+        screen.fit();
+        Ok(screen.buffer.area)
     }
 
     fn window_size(&mut self) -> Result<WindowSize, io::Error> {
-        let canvas = self.context.canvas().unwrap();
-        let rect = canvas.get_bounding_client_rect();
-        let res = window().unwrap().device_pixel_ratio();
-        let width = rect.width();
-        let height = rect.height();
+        let screen = self.screen.borrow();
+        let canvas = screen.canvas();
 
         Ok(WindowSize {
             columns_rows: Size {
-                width: (30) as u16,
-                height: (10) as u16,
+                width: screen.buffer.area.width,
+                height: screen.buffer.area.height,
             },
             pixels: Size {
-                width: (width * res) as u16,
-                height: (height * res) as u16,
+                width: canvas.width() as u16,
+                height: canvas.height() as u16,
             },
         })
     }
@@ -283,14 +730,16 @@ where
 /// The `ModifierDiff` struct is used to calculate the difference between two `Modifier`
 /// values. This is useful when updating the terminal display, as it allows for more
 /// efficient updates by only sending the necessary changes.
+#[allow(dead_code)]
 #[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash)]
 struct ModifierDiff {
     pub from: Modifier,
     pub to: Modifier,
 }
 
+#[allow(dead_code)]
 impl ModifierDiff {
-    fn queue<W>(&self, mut w: W) -> io::Result<()>
+    fn queue<W>(&self, _w: W) -> io::Result<()>
     where
         W: io::Write,
     {
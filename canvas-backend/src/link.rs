@@ -0,0 +1,374 @@
+//! Clickable hyperlinks over ranges of cells.
+
+use std::{cell::RefCell, rc::Rc};
+
+use ratatui::{buffer::Buffer, prelude::Rect};
+use web_sys::{window, MouseEvent};
+
+use crate::{dom::Listener, screen::Screen};
+
+/// The URL schemes links may use by default.
+pub const DEFAULT_LINK_SCHEMES: [&str; 3] = ["http", "https", "mailto"];
+
+/// The cells a hyperlink covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LinkArea {
+    /// A rectangle of cells.
+    Rect(Rect),
+    /// The cells from `start` up to and including `end` in reading order, as (column, row),
+    /// wrapping from the end of one row to the start of the next.
+    Span {
+        /// The first cell of the link.
+        start: (u16, u16),
+        /// The last cell of the link.
+        end: (u16, u16),
+    },
+}
+
+impl LinkArea {
+    /// Returns `true` if the cell at (`x`, `y`) is part of the link.
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        match *self {
+            LinkArea::Rect(rect) => {
+                x >= rect.left() && x < rect.right() && y >= rect.top() && y < rect.bottom()
+            }
+            LinkArea::Span { start, end } => {
+                (y, x) >= (start.1, start.0) && (y, x) <= (end.1, end.0)
+            }
+        }
+    }
+}
+
+/// Identifies a link added with [`CanvasBackend::add_link`].
+///
+/// [`CanvasBackend::add_link`]: crate::CanvasBackend::add_link
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LinkId(usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Link {
+    pub(crate) area: LinkArea,
+    pub(crate) url: String,
+}
+
+/// The links on the screen, both added by the application and detected in its text.
+#[derive(Debug)]
+pub(crate) struct Links {
+    added: Vec<(LinkId, Link)>,
+    detected: Vec<Link>,
+    next_id: usize,
+    /// Whether URLs in the rendered text are turned into links.
+    detect: bool,
+    /// The allowed schemes, lowercased and followed by `:`, as URLs start with them.
+    prefixes: Vec<String>,
+    /// The cell under the mouse pointer, if it is over the grid.
+    pub(crate) pointer: Option<(u16, u16)>,
+    /// The link under the mouse pointer, which is drawn underlined.
+    pub(crate) hovered: Option<Link>,
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Links {
+            added: Vec::new(),
+            detected: Vec::new(),
+            next_id: 0,
+            detect: false,
+            prefixes: prefixes(DEFAULT_LINK_SCHEMES.map(String::from).to_vec()),
+            pointer: None,
+            hovered: None,
+        }
+    }
+}
+
+impl Links {
+    /// Adds a link to `url` over `area`, unless its scheme is not allowed.
+    pub(crate) fn add(&mut self, area: LinkArea, url: &str) -> Option<LinkId> {
+        if !self.allows(url) {
+            return None;
+        }
+        let id = LinkId(self.next_id);
+        self.next_id += 1;
+        self.added.push((
+            id,
+            Link {
+                area,
+                url: url.to_string(),
+            },
+        ));
+        Some(id)
+    }
+
+    /// Removes the link `id` and returns it.
+    pub(crate) fn remove(&mut self, id: LinkId) -> Option<Link> {
+        let index = self.added.iter().position(|(link, _)| *link == id)?;
+        Some(self.added.remove(index).1)
+    }
+
+    /// Removes every link added by the application.
+    pub(crate) fn clear(&mut self) {
+        self.added.clear();
+    }
+
+    /// Allows links to use only `schemes`.
+    pub(crate) fn set_schemes(&mut self, schemes: Vec<String>) {
+        self.prefixes = prefixes(schemes);
+        let prefixes = &self.prefixes;
+        self.added.retain(|(_, link)| allows(prefixes, &link.url));
+    }
+
+    /// Returns the link covering the cell at (`x`, `y`), preferring added links over detected
+    /// ones.
+    pub(crate) fn at(&self, x: u16, y: u16) -> Option<&Link> {
+        self.added
+            .iter()
+            .rev()
+            .map(|(_, link)| link)
+            .chain(&self.detected)
+            .find(|link| link.area.contains(x, y))
+    }
+
+    /// Returns `true` if the cell at (`x`, `y`) belongs to the hovered link.
+    pub(crate) fn hovers(&self, x: u16, y: u16) -> bool {
+        self.hovered
+            .as_ref()
+            .is_some_and(|link| link.area.contains(x, y))
+    }
+
+    /// Turns URLs in the rendered text into links, or stops doing so.
+    pub(crate) fn set_detect(&mut self, detect: bool) {
+        self.detect = detect;
+    }
+
+    /// Detects the URLs in each row of `buffer` again, if detection is enabled.
+    pub(crate) fn detect(&mut self, buffer: &Buffer) {
+        self.detected.clear();
+        if !self.detect {
+            return;
+        }
+        let width = usize::from(buffer.area.width);
+        if width == 0 {
+            return;
+        }
+        for (y, row) in (0..).zip(buffer.content.chunks(width)) {
+            let mut x = 0;
+            while x < row.len() {
+                match self.url_at(row, x) {
+                    Some(end) => {
+                        let url: String = row[x..end].iter().map(|c| c.symbol.as_str()).collect();
+                        self.detected.push(Link {
+                            area: LinkArea::Span {
+                                start: (x as u16, y),
+                                end: (end as u16 - 1, y),
+                            },
+                            url,
+                        });
+                        x = end;
+                    }
+                    None => x += 1,
+                }
+            }
+        }
+    }
+
+    /// Returns the end of a URL starting at cell `start` of `row`, if one does.
+    fn url_at(&self, row: &[ratatui::buffer::Cell], start: usize) -> Option<usize> {
+        // A URL starts a word.
+        if start > 0 && is_url_symbol(&row[start - 1].symbol) {
+            return None;
+        }
+        let prefix = self.prefixes.iter().find(|prefix| {
+            row.len() - start >= prefix.len()
+                && prefix
+                    .chars()
+                    .zip(&row[start..])
+                    .all(|(c, cell)| symbol_char(&cell.symbol).to_ascii_lowercase() == c)
+        })?;
+
+        let body = start + prefix.len();
+        let mut end = body;
+        while end < row.len() && is_url_symbol(&row[end].symbol) {
+            end += 1;
+        }
+        // Leave out punctuation that more likely ends the surrounding sentence.
+        while end > body
+            && matches!(
+                row[end - 1].symbol.as_str(),
+                "." | "," | ";" | ":" | "!" | "?" | ")" | "]" | "'" | "\""
+            )
+        {
+            end -= 1;
+        }
+        (end > body).then_some(end)
+    }
+
+    /// Returns `true` if `url` may be opened.
+    pub(crate) fn allows(&self, url: &str) -> bool {
+        allows(&self.prefixes, url)
+    }
+}
+
+/// Returns the prefixes URLs of `schemes` start with, e.g. `https:`.
+fn prefixes(schemes: Vec<String>) -> Vec<String> {
+    schemes
+        .into_iter()
+        .map(|scheme| scheme.to_ascii_lowercase() + ":")
+        .collect()
+}
+
+/// Returns `true` if `url` is relative or starts with one of `prefixes`, ignoring case.
+fn allows(prefixes: &[String], url: &str) -> bool {
+    // Browsers ignore leading spaces and control characters as well as tabs and newlines
+    // anywhere, so `" java\tscript:"` is still a `javascript:` URL.
+    let url: String = url
+        .trim_start_matches(|c: char| c <= ' ')
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    match url.find([':', '/', '?', '#']) {
+        Some(i) if url[i..].starts_with(':') => {
+            let prefix = url[..=i].to_ascii_lowercase();
+            prefixes.contains(&prefix)
+        }
+        _ => true,
+    }
+}
+
+/// Returns the character of a single character cell `symbol`, or NUL for any other.
+fn symbol_char(symbol: &str) -> char {
+    let mut chars = symbol.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => c,
+        _ => '\0',
+    }
+}
+
+fn is_url_symbol(symbol: &str) -> bool {
+    let mut chars = symbol.chars();
+    matches!((chars.next(), chars.next()), (Some(c), None) if c.is_ascii_graphic()
+        && !matches!(c, '<' | '>' | '"' | '`' | '{' | '}' | '|' | '\\' | '^'))
+}
+
+/// Underlines links under the mouse pointer, shows a pointer cursor over them and opens them
+/// in a new tab when clicked.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let canvas = screen.borrow().canvas();
+
+    let hover = {
+        let screen = Rc::clone(screen);
+        Listener::new(&canvas, "mousemove", move |event: MouseEvent| {
+            let mut screen = screen.borrow_mut();
+            let pointer = screen.cell_at(f64::from(event.client_x()), f64::from(event.client_y()));
+            screen.update_links(|links| links.pointer = pointer);
+        })
+    };
+
+    let leave = {
+        let screen = Rc::clone(screen);
+        Listener::new(&canvas, "mouseleave", move |_: MouseEvent| {
+            screen
+                .borrow_mut()
+                .update_links(|links| links.pointer = None);
+        })
+    };
+
+    let click = {
+        let screen = Rc::clone(screen);
+        Listener::active(&canvas, "click", move |event: MouseEvent| {
+            let screen = screen.borrow();
+            let Some(link) = &screen.links.hovered else {
+                return;
+            };
+            // A drag that selected text ends in a click too.
+            if screen.selection.is_some() {
+                return;
+            }
+            if screen.links.allows(&link.url) {
+                event.prevent_default();
+                let _ = window().unwrap().open_with_url_and_target_and_features(
+                    &link.url,
+                    "_blank",
+                    "noopener,noreferrer",
+                );
+            }
+        })
+    };
+
+    vec![hover, leave, click]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links() -> Links {
+        Links {
+            detect: true,
+            ..Links::default()
+        }
+    }
+
+    #[test]
+    fn allows_default_schemes() {
+        let links = links();
+        assert!(links.allows("https://example.com"));
+        assert!(links.allows("HTTP://example.com"));
+        assert!(links.allows("https://"));
+        assert!(links.allows("mailto:"));
+    }
+
+    #[test]
+    fn allows_relative_urls() {
+        let links = links();
+        assert!(links.allows("docs/index.html"));
+        assert!(links.allows("/search?q=a:b"));
+        assert!(links.allows("#top"));
+        assert!(links.allows(""));
+    }
+
+    #[test]
+    fn rejects_disguised_schemes() {
+        let links = links();
+        assert!(!links.allows("javascript:alert(1)"));
+        assert!(!links.allows("JAVASCRIPT:alert(1)"));
+        assert!(!links.allows(" java\tscript:alert(1)"));
+        assert!(!links.allows("\u{1}javascript:alert(1)"));
+        assert!(!links.allows("java\nscript:alert(1)"));
+        assert!(!links.allows("data:text/html,<script>"));
+    }
+
+    #[test]
+    fn set_schemes_ignores_case() {
+        let mut links = links();
+        links.set_schemes(vec!["FTP".to_string()]);
+        assert!(links.allows("ftp://example.com"));
+        assert!(!links.allows("https://example.com"));
+    }
+
+    #[test]
+    fn detects_urls_in_text() {
+        let mut links = links();
+        links.detect(&Buffer::with_lines(vec![
+            "see HTTPS://example.com/a. or x:y",
+        ]));
+        assert_eq!(
+            links.detected,
+            [Link {
+                area: LinkArea::Span {
+                    start: (4, 0),
+                    end: (24, 0),
+                },
+                url: "HTTPS://example.com/a".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn skips_urls_inside_words_and_bare_schemes() {
+        let mut links = links();
+        links.detect(&Buffer::with_lines(vec![
+            "xhttps://example.com https: mailto",
+        ]));
+        assert!(links.detected.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+//! Cell metrics measured from the font in use.
+
+use ratatui::prelude::Rect;
+use web_sys::CanvasRenderingContext2d;
+
+use crate::font::Font;
+
+/// How the size of a cell is derived.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CellGeometry {
+    /// Cells as wide as a glyph of the font and as tall as its line height.
+    #[default]
+    Font,
+    /// Cells `ratio` times as wide as they are tall, e.g. `1.0` for square cells.
+    ///
+    /// The cells are the smallest ones with that aspect that still fit a glyph of the font.
+    AspectRatio(f64),
+    /// Cells of exactly `width` x `height` CSS pixels, regardless of the font size.
+    Fixed {
+        /// The width of a cell.
+        width: f64,
+        /// The height of a cell.
+        height: f64,
+    },
+}
+
+/// The size of a single cell and where glyphs sit inside it, in CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellMetrics {
+    /// The width of a cell.
+    pub width: f64,
+    /// The height of a cell.
+    pub height: f64,
+    /// The distance from the top of a cell to the text baseline.
+    pub baseline: f64,
+    /// The distance from the left of a cell to where its glyph starts.
+    pub glyph_offset: f64,
+}
+
+impl CellMetrics {
+    /// Measures `font` using `context` and shapes the cells according to `geometry`, centring
+    /// glyphs in cells that are larger than them.
+    pub(crate) fn measure(
+        context: &CanvasRenderingContext2d,
+        font: &Font,
+        geometry: CellGeometry,
+    ) -> CellMetrics {
+        CellMetrics::shape(CellMetrics::measure_font(context, font), geometry)
+    }
+
+    /// Shapes cells that fit a `glyph` of the font according to `geometry`.
+    fn shape(glyph: CellMetrics, geometry: CellGeometry) -> CellMetrics {
+        let (width, height) = match geometry {
+            CellGeometry::Font => return glyph,
+            CellGeometry::AspectRatio(ratio) if ratio > 0.0 => {
+                if glyph.width / glyph.height < ratio {
+                    (glyph.height * ratio, glyph.height)
+                } else {
+                    (glyph.width, (glyph.width / ratio).ceil())
+                }
+            }
+            CellGeometry::Fixed { width, height } if width > 0.0 && height > 0.0 => (width, height),
+            CellGeometry::AspectRatio(_) | CellGeometry::Fixed { .. } => return glyph,
+        };
+
+        CellMetrics {
+            width,
+            height,
+            baseline: glyph.baseline + ((height - glyph.height) / 2.0).round(),
+            // Glyphs wider than fixed cells start at the left edge like in cells shaped by the
+            // font, overflowing to the right.
+            glyph_offset: ((width - glyph.width) / 2.0).max(0.0),
+        }
+    }
+
+    /// Measures the advance and line height of `font` using `context`.
+    ///
+    /// Falls back to proportions of the font size if the browser reports no metrics, e.g.
+    /// while the canvas is detached.
+    fn measure_font(context: &CanvasRenderingContext2d, font: &Font) -> CellMetrics {
+        context.set_font(&font.css());
+        let (width, ascent, descent) = match context.measure_text("M") {
+            Ok(metrics) => (
+                metrics.width(),
+                metrics.font_bounding_box_ascent(),
+                metrics.font_bounding_box_descent(),
+            ),
+            Err(_) => (0.0, 0.0, 0.0),
+        };
+
+        let width = if width > 0.0 { width } else { font.size * 0.6 };
+        let (ascent, descent) = if ascent + descent > 0.0 {
+            (ascent, descent)
+        } else {
+            (font.size * 0.8, font.size * 0.2)
+        };
+
+        CellMetrics {
+            width,
+            height: (ascent + descent).ceil(),
+            baseline: ascent.round(),
+            glyph_offset: 0.0,
+        }
+    }
+
+    /// Returns the grid that fits into `width` x `height` CSS pixels.
+    pub(crate) fn grid(&self, width: f64, height: f64) -> Rect {
+        Rect::new(
+            0,
+            0,
+            (width / self.width).max(0.0) as u16,
+            (height / self.height).max(0.0) as u16,
+        )
+    }
+
+    /// Returns the top left corner of the cell at (`x`, `y`).
+    pub(crate) fn origin(&self, x: u16, y: u16) -> (f64, f64) {
+        (f64::from(x) * self.width, f64::from(y) * self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLYPH: CellMetrics = CellMetrics {
+        width: 9.6,
+        height: 19.0,
+        baseline: 15.0,
+        glyph_offset: 0.0,
+    };
+
+    #[test]
+    fn font_cells_are_the_glyph() {
+        assert_eq!(CellMetrics::shape(GLYPH, CellGeometry::Font), GLYPH);
+    }
+
+    #[test]
+    fn square_cells_widen_narrow_glyphs() {
+        let cells = CellMetrics::shape(GLYPH, CellGeometry::AspectRatio(1.0));
+        assert_eq!((cells.width, cells.height), (19.0, 19.0));
+        assert_eq!(cells.baseline, 15.0);
+        assert!((cells.glyph_offset - 4.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn narrow_cells_heighten_wide_glyphs() {
+        let cells = CellMetrics::shape(GLYPH, CellGeometry::AspectRatio(0.25));
+        assert_eq!((cells.width, cells.height), (9.6, 39.0));
+        assert_eq!(cells.baseline, 25.0);
+        assert_eq!(cells.glyph_offset, 0.0);
+    }
+
+    #[test]
+    fn fixed_cells_centre_glyphs() {
+        let cells = CellMetrics::shape(
+            GLYPH,
+            CellGeometry::Fixed {
+                width: 12.0,
+                height: 25.0,
+            },
+        );
+        assert_eq!((cells.width, cells.height), (12.0, 25.0));
+        assert_eq!(cells.baseline, 18.0);
+        assert!((cells.glyph_offset - 1.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fixed_cells_narrower_than_glyphs_start_them_at_the_left_edge() {
+        let cells = CellMetrics::shape(
+            GLYPH,
+            CellGeometry::Fixed {
+                width: 6.0,
+                height: 19.0,
+            },
+        );
+        assert_eq!(cells.glyph_offset, 0.0);
+    }
+
+    #[test]
+    fn invalid_geometry_falls_back_to_the_font() {
+        assert_eq!(
+            CellMetrics::shape(GLYPH, CellGeometry::AspectRatio(0.0)),
+            GLYPH
+        );
+        assert_eq!(
+            CellMetrics::shape(
+                GLYPH,
+                CellGeometry::Fixed {
+                    width: -1.0,
+                    height: 10.0,
+                }
+            ),
+            GLYPH
+        );
+    }
+
+    #[test]
+    fn grid_fits_whole_cells() {
+        assert_eq!(GLYPH.grid(100.0, 40.0), Rect::new(0, 0, 10, 2));
+        assert_eq!(GLYPH.grid(-5.0, 0.0), Rect::new(0, 0, 0, 0));
+        assert_eq!(GLYPH.origin(2, 3), (19.2, 57.0));
+    }
+}
@@ -0,0 +1,230 @@
+//! Mouse input over the canvas, reported as events of the cells under the pointer.
+
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::{PointerEvent, WheelEvent};
+
+use crate::{
+    dom::Listener,
+    event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+    screen::Screen,
+};
+
+/// The state shared by the mouse listeners.
+#[derive(Debug, Default)]
+struct Pointer {
+    /// The buttons pressed over the grid and still held down, as a DOM `buttons` mask.
+    buttons: u16,
+    /// The cell of the last event, so moves within a cell are not reported.
+    cell: Option<(u16, u16)>,
+    /// The wheel scrolling not reported yet, in rows and columns.
+    scroll: (f64, f64),
+}
+
+impl Pointer {
+    /// Returns the button reported for a drag, the first one held down.
+    fn held(&self) -> Option<MouseButton> {
+        [MouseButton::Left, MouseButton::Right, MouseButton::Middle]
+            .into_iter()
+            .find(|button| self.buttons & button.mask() != 0)
+    }
+}
+
+fn emit(
+    screen: &Screen,
+    kind: MouseEventKind,
+    (column, row): (u16, u16),
+    event: &web_sys::MouseEvent,
+) {
+    let modifiers = KeyModifiers::from_keys(
+        event.shift_key(),
+        event.ctrl_key(),
+        event.alt_key(),
+        event.meta_key(),
+    );
+    screen.on_event.emit(Event::Mouse(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers,
+    }));
+}
+
+/// Reports presses, releases, drags, moves and wheel scrolling over the canvas of `screen`.
+///
+/// The pointer is captured while a button is held down, so a drag keeps being reported, at the
+/// nearest cell, once it leaves the canvas.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let canvas = screen.borrow().canvas();
+    let pointer = Rc::new(RefCell::new(Pointer::default()));
+
+    let down = {
+        let screen = Rc::clone(screen);
+        let pointer = Rc::clone(&pointer);
+        let target = canvas.clone();
+        Listener::new(&canvas, "pointerdown", move |event: PointerEvent| {
+            // Shift+drag selects text instead, like in a terminal.
+            if event.pointer_type() == "touch" || event.shift_key() {
+                return;
+            }
+            let Some(button) = MouseButton::from_dom(event.button()) else {
+                return;
+            };
+            let screen = screen.borrow();
+            let Some(cell) = screen.cell_at(event.client_x().into(), event.client_y().into())
+            else {
+                return;
+            };
+            let _ = target.set_pointer_capture(event.pointer_id());
+            let mut pointer = pointer.borrow_mut();
+            pointer.buttons |= button.mask();
+            pointer.cell = Some(cell);
+            emit(&screen, MouseEventKind::Down(button), cell, &event);
+        })
+    };
+
+    let moved = {
+        let screen = Rc::clone(screen);
+        let pointer = Rc::clone(&pointer);
+        Listener::new(&canvas, "pointermove", move |event: PointerEvent| {
+            if event.pointer_type() == "touch" {
+                return;
+            }
+            let screen = screen.borrow();
+            let mut pointer = pointer.borrow_mut();
+            let (x, y) = (event.client_x().into(), event.client_y().into());
+
+            // Pressing or releasing a button while another is held down only fires a move.
+            if let Some(button) = MouseButton::from_dom(event.button()) {
+                let pressed = event.buttons() & button.mask() != 0;
+                let held = pointer.buttons & button.mask() != 0;
+                let Some(cell) = screen.nearest_cell(x, y) else {
+                    return;
+                };
+                let kind = match (pressed, held) {
+                    (true, false) => MouseEventKind::Down(button),
+                    (false, true) => MouseEventKind::Up(button),
+                    _ => return,
+                };
+                pointer.buttons ^= button.mask();
+                pointer.cell = Some(cell);
+                emit(&screen, kind, cell, &event);
+                return;
+            }
+
+            let held = pointer.held();
+            let cell = match held {
+                Some(_) => screen.nearest_cell(x, y),
+                None => screen.cell_at(x, y),
+            };
+            if cell == pointer.cell {
+                return;
+            }
+            pointer.cell = cell;
+            if let Some(cell) = cell {
+                let kind = held.map_or(MouseEventKind::Moved, MouseEventKind::Drag);
+                emit(&screen, kind, cell, &event);
+            }
+        })
+    };
+
+    let up = {
+        let screen = Rc::clone(screen);
+        let pointer = Rc::clone(&pointer);
+        let target = canvas.clone();
+        Listener::new(&canvas, "pointerup", move |event: PointerEvent| {
+            if event.pointer_type() == "touch" {
+                return;
+            }
+            let Some(button) = MouseButton::from_dom(event.button()) else {
+                return;
+            };
+            let mut pointer = pointer.borrow_mut();
+            // Presses outside of the grid are not reported, so neither are their releases.
+            if pointer.buttons & button.mask() == 0 {
+                return;
+            }
+            pointer.buttons &= !button.mask();
+            let _ = target.release_pointer_capture(event.pointer_id());
+            let screen = screen.borrow();
+            let (x, y) = (event.client_x().into(), event.client_y().into());
+            if let Some(cell) = screen.nearest_cell(x, y) {
+                pointer.cell = Some(cell);
+                emit(&screen, MouseEventKind::Up(button), cell, &event);
+            }
+        })
+    };
+
+    let cancel = {
+        let pointer = Rc::clone(&pointer);
+        Listener::new(&canvas, "pointercancel", move |_: PointerEvent| {
+            pointer.borrow_mut().buttons = 0;
+        })
+    };
+
+    let wheel = {
+        let screen = Rc::clone(screen);
+        let pointer = Rc::clone(&pointer);
+        Listener::active(&canvas, "wheel", move |event: WheelEvent| {
+            // Ctrl+wheel zooms instead.
+            if event.ctrl_key() {
+                return;
+            }
+            let screen = screen.borrow();
+            let Some(cell) = screen.cell_at(event.client_x().into(), event.client_y().into())
+            else {
+                return;
+            };
+            event.prevent_default();
+
+            let area = screen.buffer.area;
+            let (rows, columns) = match event.delta_mode() {
+                WheelEvent::DOM_DELTA_PIXEL => (
+                    event.delta_y() / screen.metrics.height,
+                    event.delta_x() / screen.metrics.width,
+                ),
+                WheelEvent::DOM_DELTA_LINE => (event.delta_y(), event.delta_x()),
+                _ => (
+                    event.delta_y() * f64::from(area.height),
+                    event.delta_x() * f64::from(area.width),
+                ),
+            };
+
+            let mut pointer = pointer.borrow_mut();
+            let (pending_rows, pending_columns) = &mut pointer.scroll;
+            for (delta, pending, forward, backward) in [
+                (
+                    rows,
+                    pending_rows,
+                    MouseEventKind::ScrollDown,
+                    MouseEventKind::ScrollUp,
+                ),
+                (
+                    columns,
+                    pending_columns,
+                    MouseEventKind::ScrollRight,
+                    MouseEventKind::ScrollLeft,
+                ),
+            ] {
+                // Start over when the direction changes, so a reversal is reported right away.
+                if delta * *pending < 0.0 {
+                    *pending = 0.0;
+                }
+                // Touchpads scroll by a few pixels at a time, so add up to whole steps.
+                *pending += delta;
+                while pending.abs() >= 1.0 {
+                    let kind = if *pending > 0.0 { forward } else { backward };
+                    *pending -= pending.signum();
+                    emit(&screen, kind, cell, &event);
+                }
+            }
+        })
+    };
+
+    // The right button is reported to the application rather than opening the context menu.
+    let menu = Listener::active(&canvas, "contextmenu", |event: web_sys::Event| {
+        event.prevent_default();
+    });
+
+    vec![down, moved, up, cancel, wheel, menu]
+}
@@ -0,0 +1,208 @@
+//! Integration with the page around the canvas: its title, fullscreen and favicon.
+
+use std::{cell::RefCell, io, rc::Rc};
+
+use wasm_bindgen::JsCast;
+use web_sys::{
+    window, CanvasRenderingContext2d, Document, Element, HtmlCanvasElement, HtmlImageElement,
+};
+
+use crate::{dom::Listener, screen::Screen};
+
+/// The size the badged favicon is drawn at, in pixels.
+const ICON_SIZE: u32 = 32;
+
+/// The colour of the activity dot on the favicon.
+const BADGE_COLOR: &str = "#e53935";
+
+fn document() -> Document {
+    window().unwrap().document().unwrap()
+}
+
+/// Sets the title of the page, shown on its tab.
+pub(crate) fn set_title(title: &str) {
+    document().set_title(title);
+}
+
+/// Returns `true` if `canvas` is shown fullscreen.
+pub(crate) fn is_fullscreen(canvas: &HtmlCanvasElement) -> bool {
+    document()
+        .fullscreen_element()
+        .is_some_and(|element| element == ***canvas)
+}
+
+/// Shows `canvas` fullscreen, or leaves fullscreen.
+pub(crate) fn set_fullscreen(canvas: &HtmlCanvasElement, fullscreen: bool) -> io::Result<()> {
+    if fullscreen == is_fullscreen(canvas) {
+        return Ok(());
+    }
+    if fullscreen {
+        canvas
+            .request_fullscreen()
+            .map_err(|err| io::Error::other(format!("fullscreen request failed: {err:?}")))
+    } else {
+        document().exit_fullscreen();
+        Ok(())
+    }
+}
+
+/// How to undo showing the dot on an icon link.
+#[derive(Debug)]
+enum Restore {
+    /// Set the `href` back to the original one.
+    Href(String),
+    /// Remove the `href` the link did not have.
+    RemoveHref,
+    /// Remove the link, which was created for the dot.
+    RemoveLink,
+}
+
+/// A dot on the favicon telling that there is new output while the tab is in the background.
+#[derive(Debug, Default)]
+pub(crate) struct Badge {
+    enabled: bool,
+    /// The icon link the dot is shown on, while it is, and how to restore it.
+    shown: Option<(Element, Restore)>,
+    /// The original favicon, loaded to draw the dot onto.
+    icon: Option<HtmlImageElement>,
+}
+
+impl Badge {
+    /// Enables or disables the badge, restoring the original favicon when disabled.
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+            self.icon = None;
+            return;
+        }
+        let href = icon_link().and_then(|link| link.get_attribute("href"));
+        self.icon = href.and_then(|href| {
+            let icon = HtmlImageElement::new().ok()?;
+            // Without CORS the badged icon cannot be exported and only the dot is shown.
+            icon.set_cross_origin(Some("anonymous"));
+            icon.set_src(&href);
+            Some(icon)
+        });
+    }
+
+    /// Notes new output, showing the dot if the tab is in the background.
+    pub(crate) fn activity(&mut self) {
+        if !self.enabled || self.shown.is_some() || !document().hidden() {
+            return;
+        }
+        let Some(href) = self.badged_icon() else {
+            return;
+        };
+        let (link, restore) = match icon_link() {
+            Some(link) => {
+                let restore = link
+                    .get_attribute("href")
+                    .map_or(Restore::RemoveHref, Restore::Href);
+                (link, restore)
+            }
+            None => match create_icon_link() {
+                Some(link) => (link, Restore::RemoveLink),
+                None => return,
+            },
+        };
+        let _ = link.set_attribute("href", &href);
+        self.shown = Some((link, restore));
+    }
+
+    /// Removes the dot, e.g. because the tab has become visible.
+    pub(crate) fn clear(&mut self) {
+        match self.shown.take() {
+            Some((link, Restore::Href(href))) => {
+                let _ = link.set_attribute("href", &href);
+            }
+            Some((link, Restore::RemoveHref)) => {
+                let _ = link.remove_attribute("href");
+            }
+            Some((link, Restore::RemoveLink)) => link.remove(),
+            None => {}
+        }
+    }
+
+    /// Returns a data URL of the original favicon with the dot drawn onto it.
+    fn badged_icon(&self) -> Option<String> {
+        let canvas = document()
+            .create_element("canvas")
+            .ok()?
+            .dyn_into::<HtmlCanvasElement>()
+            .ok()?;
+        canvas.set_width(ICON_SIZE);
+        canvas.set_height(ICON_SIZE);
+        let context = canvas
+            .get_context("2d")
+            .ok()??
+            .dyn_into::<CanvasRenderingContext2d>()
+            .ok()?;
+
+        let size = f64::from(ICON_SIZE);
+        let dot = |context: &CanvasRenderingContext2d| {
+            context.begin_path();
+            let _ = context.arc(
+                size * 0.7,
+                size * 0.3,
+                size * 0.3,
+                0.0,
+                std::f64::consts::TAU,
+            );
+            context.set_fill_style_str(BADGE_COLOR);
+            context.fill();
+        };
+
+        if let Some(icon) = self.icon.as_ref().filter(|icon| icon.natural_width() > 0) {
+            let _ = context
+                .draw_image_with_html_image_element_and_dw_and_dh(icon, 0.0, 0.0, size, size);
+            dot(&context);
+            if let Ok(url) = canvas.to_data_url() {
+                return Some(url);
+            }
+            // The icon tainted the canvas, so draw the dot alone.
+            context.clear_rect(0.0, 0.0, size, size);
+        }
+        dot(&context);
+        canvas.to_data_url().ok()
+    }
+}
+
+/// Returns the `<link rel="icon">` of the page, if it has one.
+fn icon_link() -> Option<Element> {
+    document().query_selector("link[rel~='icon']").ok()?
+}
+
+/// Adds a `<link rel="icon">` to the head of the page.
+fn create_icon_link() -> Option<Element> {
+    let document = document();
+    let link = document.create_element("link").ok()?;
+    link.set_attribute("rel", "icon").ok()?;
+    document.head()?.append_child(&link).ok()?;
+    Some(link)
+}
+
+/// Fits the grid to the canvas when it enters or leaves fullscreen, and removes the favicon
+/// badge once the tab is visible again.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let document = document();
+
+    let fullscreen = {
+        let screen = Rc::clone(screen);
+        Listener::new(&document, "fullscreenchange", move |_: web_sys::Event| {
+            screen.borrow_mut().fit();
+        })
+    };
+
+    let visibility = {
+        let screen = Rc::clone(screen);
+        let page = document.clone();
+        Listener::new(&document, "visibilitychange", move |_: web_sys::Event| {
+            if !page.hidden() {
+                screen.borrow_mut().badge.clear();
+            }
+        })
+    };
+
+    vec![fullscreen, visibility]
+}
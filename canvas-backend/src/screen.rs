@@ -0,0 +1,1051 @@
+//! The rendering state shared between a [`CanvasBackend`](crate::CanvasBackend) and the DOM
+//! callbacks it registers.
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    prelude::Rect,
+    style::{Color, Modifier},
+};
+use unicode_width::UnicodeWidthStr;
+use wasm_bindgen::JsCast;
+use web_sys::{window, CanvasRenderingContext2d, EventTarget, HtmlCanvasElement, HtmlElement};
+
+use crate::{
+    bell::Bell,
+    blink::{Blink, BLINKING},
+    cursor::{Cursor, CursorShape},
+    dom::Hook,
+    event::{Event, Events},
+    font::Font,
+    glyph::Glyphs,
+    image::{Image, Images},
+    ime::{self, Ime},
+    keyboard::{KeyBinding, PreventDefault, DEFAULT_PASSTHROUGH},
+    link::Links,
+    metrics::{CellGeometry, CellMetrics},
+    page::Badge,
+    scrollback::Scrollback,
+    selection::Selection,
+    theme::Theme,
+    zoom::{MAX_FONT_SIZE, MIN_FONT_SIZE},
+};
+
+/// How changes to the cells are painted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Batch {
+    /// Each change is painted as it is made.
+    Off,
+    /// Changes are batched, and painted as they are made while the canvas matches the cells.
+    Painting,
+    /// Changes are batched, and cells were moved since the batch started, so the canvas is
+    /// repainted at its end instead.
+    Stale,
+}
+
+/// The canvas together with a copy of every cell painted on it.
+///
+/// Keeping the cells around lets the screen be repainted without the application, e.g. once a
+/// web font has loaded.
+#[derive(Debug)]
+pub(crate) struct Screen {
+    pub(crate) context: CanvasRenderingContext2d,
+    pub(crate) font: Font,
+    pub(crate) geometry: CellGeometry,
+    pub(crate) metrics: CellMetrics,
+    /// The number of device pixels per CSS pixel the canvas is backed by.
+    pub(crate) scale: f64,
+    pub(crate) glyphs: Glyphs,
+    pub(crate) theme: Theme,
+    pub(crate) buffer: Buffer,
+    pub(crate) scrollback: Scrollback,
+    pub(crate) cursor: Cursor,
+    pub(crate) blink: Blink,
+    /// Whether a blinking cell may be shown: set when one is painted and cleared once
+    /// [`blink`](Self::blink) finds none, so screens without any are not scanned on each tick.
+    blinking: std::cell::Cell<bool>,
+    pub(crate) links: Links,
+    pub(crate) images: Images,
+    /// The cells selected on the view, cleared once any of them changes or the view scrolls.
+    pub(crate) selection: Option<Selection>,
+    pub(crate) ime: Ime,
+    pub(crate) bell: Bell,
+    pub(crate) badge: Badge,
+    /// The number of fonts being waited on. Painting is deferred while this is non-zero.
+    pub(crate) pending_fonts: usize,
+    /// Whether changes are applied as a batch, see [`batch`](Self::batch).
+    batch: Batch,
+    /// Called with the new grid whenever the number of cells changes.
+    pub(crate) on_resize: Hook<Rect>,
+    /// Called with every input event.
+    pub(crate) on_event: Events,
+    /// Whether the application takes input, i.e. has registered for events, so keys and
+    /// pasted text are reported with the browser's default action suppressed.
+    pub(crate) capture: bool,
+    /// Whether mouse events are reported rather than used by the backend, e.g. to scroll.
+    pub(crate) mouse_capture: bool,
+    /// Which keys the browser's default action is suppressed for.
+    pub(crate) prevent_default: PreventDefault,
+    /// The keys left to the browser rather than reported.
+    pub(crate) passthrough: Vec<KeyBinding>,
+}
+
+impl Screen {
+    pub(crate) fn new(context: CanvasRenderingContext2d) -> Screen {
+        let font = Font::default();
+        let geometry = CellGeometry::default();
+        let metrics = CellMetrics::measure(&context, &font, geometry);
+        let mut screen = Screen {
+            context,
+            font,
+            geometry,
+            metrics,
+            scale: 1.0,
+            glyphs: Glyphs::new(),
+            theme: Theme::default(),
+            buffer: Buffer::default(),
+            scrollback: Scrollback::default(),
+            cursor: Cursor::default(),
+            blink: Blink::default(),
+            blinking: std::cell::Cell::new(false),
+            links: Links::default(),
+            images: Images::default(),
+            selection: None,
+            ime: Ime::default(),
+            bell: Bell::default(),
+            badge: Badge::default(),
+            pending_fonts: 0,
+            batch: Batch::Off,
+            on_resize: Hook::default(),
+            on_event: Events::default(),
+            mouse_capture: false,
+            capture: false,
+            prevent_default: PreventDefault::default(),
+            passthrough: DEFAULT_PASSTHROUGH.to_vec(),
+        };
+        screen.fit();
+        screen
+    }
+
+    pub(crate) fn canvas(&self) -> HtmlCanvasElement {
+        self.context.canvas().unwrap()
+    }
+
+    /// Sizes the canvas to its layout size in device pixels and the buffer to the grid that
+    /// fits into it.
+    pub(crate) fn fit(&mut self) {
+        let canvas = self.canvas();
+        let rect = canvas.get_bounding_client_rect();
+        let scale = window().unwrap().device_pixel_ratio();
+        let width = (rect.width() * scale).round() as u32;
+        let height = (rect.height() * scale).round() as u32;
+
+        let area = self.metrics.grid(rect.width(), rect.height());
+        if area != self.buffer.area {
+            // Keep cells where they are rather than reflowing them by index, so the screen
+            // stays readable until the application redraws at the new size.
+            self.buffer = resized(&self.buffer, area);
+            self.selection = None;
+            self.detect_links();
+            self.on_resize.emit(area);
+            self.on_event.emit(Event::Resize(area.width, area.height));
+        }
+
+        // Resizing the canvas clears it, so only do so when the layout actually changed.
+        if canvas.width() != width || canvas.height() != height || self.scale != scale {
+            canvas.set_width(width);
+            canvas.set_height(height);
+            self.scale = scale;
+            self.reset_transform();
+            self.repaint();
+        }
+    }
+
+    /// Makes the context draw in CSS pixels on the device pixel backing store.
+    fn reset_transform(&self) {
+        self.context
+            .set_transform(self.scale, 0.0, 0.0, self.scale, 0.0, 0.0)
+            .unwrap();
+    }
+
+    /// Returns the device pixel rectangle covered by the cells of `area` as
+    /// `(left, top, right, bottom)`.
+    ///
+    /// Every edge is rounded to a whole device pixel the same way for the cells on either side
+    /// of it, so neighbouring cells share their edges exactly at any device pixel ratio.
+    pub(crate) fn device_rect(&self, area: Rect) -> (f64, f64, f64, f64) {
+        let (left, top) = self.metrics.origin(area.x, area.y);
+        let (right, bottom) = self.metrics.origin(area.right(), area.bottom());
+        let snap = |v: f64| (v * self.scale).round();
+        (snap(left), snap(top), snap(right), snap(bottom))
+    }
+
+    /// Fills the cells of `area` with `color`.
+    ///
+    /// The fill is done in device pixels on snapped edges, as fractional edges would be
+    /// anti-aliased and leave hairline seams between neighbouring backgrounds.
+    pub(crate) fn fill_cells(&self, area: Rect, color: &str) {
+        let (left, top, right, bottom) = self.device_rect(area);
+        self.context
+            .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+            .unwrap();
+        self.context.set_fill_style_str(color);
+        self.context
+            .fill_rect(left, top, right - left, bottom - top);
+        self.reset_transform();
+    }
+
+    /// Measures the font again and repaints every cell with the new metrics.
+    pub(crate) fn remeasure(&mut self) {
+        self.glyphs.clear();
+        self.metrics = CellMetrics::measure(&self.context, &self.font, self.geometry);
+        self.fit();
+        self.repaint();
+    }
+
+    /// Renders cells at `size` CSS pixels, clamped to the zoom limits.
+    pub(crate) fn set_font_size(&mut self, size: f64) {
+        let size = size.clamp(MIN_FONT_SIZE, MAX_FONT_SIZE);
+        if size != self.font.size {
+            self.font.size = size;
+            self.remeasure();
+        }
+    }
+
+    /// Rings the bell.
+    pub(crate) fn ring_bell(&mut self) {
+        let canvas = self.canvas();
+        self.bell.ring(&canvas, self.blink.reduced_motion);
+    }
+
+    /// Returns `true` if cells may be painted, i.e. no font is being waited on and no batch
+    /// defers painting to its end.
+    pub(crate) fn ready(&self) -> bool {
+        self.pending_fonts == 0 && self.batch != Batch::Stale
+    }
+
+    /// Applies the changes made by `apply` as one.
+    ///
+    /// Scrolling moves the cells without painting them, and everything after it is painted
+    /// with a single repaint at the end, so output scrolling by many lines at once costs no
+    /// more than one screenful.
+    pub(crate) fn batch(&mut self, apply: impl FnOnce(&mut Screen)) {
+        self.batch = Batch::Painting;
+        apply(self);
+        if std::mem::replace(&mut self.batch, Batch::Off) == Batch::Stale {
+            self.repaint();
+        }
+    }
+
+    /// Detects links again and repaints `area` after cells were moved, or leaves both to the
+    /// end of the batch.
+    fn moved(&mut self, area: Rect) {
+        if self.batch != Batch::Off {
+            self.batch = Batch::Stale;
+            return;
+        }
+        self.detect_links();
+        self.repaint_area(area);
+    }
+
+    /// Stores `cell` at (`x`, `y`) and paints it.
+    ///
+    /// The cursor is not painted over the cell; call [`paint_cursor`](Self::paint_cursor)
+    /// once done setting cells.
+    pub(crate) fn set_cell(&mut self, x: u16, y: u16, cell: &Cell) {
+        let area = self.buffer.area;
+        if x >= area.right() || y >= area.bottom() {
+            return;
+        }
+        self.follow_output();
+        if self
+            .selection
+            .is_some_and(|selection| selection.contains(x, y, area.width))
+        {
+            self.select(None);
+        }
+        let previous = std::mem::replace(self.buffer.get_mut(x, y), cell.clone());
+        let cell_area = Rect::new(x, y, 1, 1);
+        self.remove_images(|_, image| image.area.intersects(cell_area));
+        if self.ready() {
+            // A narrower grapheme uncovers the cells the previous one spanned.
+            let width = previous.symbol.width().max(cell.symbol.width()).max(1) as u16;
+            let painted = self.paint_row(y, x, x.saturating_add(width));
+            self.paint_images(painted);
+        }
+    }
+
+    /// Moves the cursor down `n` rows like a line feed would, scrolling the content up by the
+    /// rows it would move past the bottom.
+    pub(crate) fn line_feed(&mut self, n: u16) {
+        self.follow_output();
+        let bottom = self.buffer.area.height.saturating_sub(1);
+        let down = n.min(bottom.saturating_sub(self.cursor.y));
+        self.scroll_up(n - down);
+        self.update_cursor(|cursor| cursor.y += down);
+    }
+
+    /// Scrolls the content up by `n` rows into the scrollback, clearing the rows that come in
+    /// at the bottom.
+    pub(crate) fn scroll_up(&mut self, n: u16) {
+        let area = self.buffer.area;
+        let n = n.min(area.height);
+        // A canvas narrower than a cell has rows without any cells in them.
+        if n == 0 || area.width == 0 {
+            return;
+        }
+        self.scrollback.scroll_in(&mut self.buffer, n);
+        // Images move up with their cells, and go once any part of them would leave the top.
+        self.images.remove(|_, image| image.area.y < n);
+        for image in self.images.iter_mut() {
+            image.area.y -= n;
+        }
+        self.selection = None;
+        self.moved(area);
+    }
+
+    /// Scrolls rows `top` to `bottom` up by `n` rows, or down if negative, leaving the rows
+    /// outside alone and clearing the rows that come in. Nothing is kept in the scrollback.
+    pub(crate) fn scroll_rows(&mut self, top: u16, bottom: u16, n: i32) {
+        let area = self.buffer.area;
+        let bottom = bottom.min(area.height.saturating_sub(1));
+        if top > bottom || n == 0 {
+            return;
+        }
+        self.follow_output();
+        let width = usize::from(area.width);
+        let rows =
+            &mut self.buffer.content[usize::from(top) * width..(usize::from(bottom) + 1) * width];
+        let shift = (n.unsigned_abs() as usize * width).min(rows.len());
+        if n > 0 {
+            rows.rotate_left(shift);
+            let len = rows.len();
+            rows[len - shift..].iter_mut().for_each(Cell::reset);
+        } else {
+            rows.rotate_right(shift);
+            rows[..shift].iter_mut().for_each(Cell::reset);
+        }
+
+        let region = Rect::new(0, top, area.width, bottom - top + 1);
+        if self
+            .selection
+            .is_some_and(|selection| selection.rows(area.width).intersects(region))
+        {
+            self.select(None);
+        }
+        self.images.remove(|_, image| image.area.intersects(region));
+        self.moved(region);
+    }
+
+    /// Replaces the cells with `buffer`, fitted to the grid, and repaints the canvas. Returns
+    /// the cells replaced.
+    pub(crate) fn swap_buffer(&mut self, buffer: Buffer) -> Buffer {
+        self.follow_output();
+        let buffer = resized(&buffer, self.buffer.area);
+        let previous = std::mem::replace(&mut self.buffer, buffer);
+        self.images.remove(|_, _| true);
+        self.selection = None;
+        self.detect_links();
+        self.repaint();
+        previous
+    }
+
+    /// Resets the cells from `start` up to and including `end`, in reading order, and fills
+    /// them with the theme background.
+    pub(crate) fn clear(&mut self, start: (u16, u16), end: (u16, u16)) {
+        let area = self.buffer.area;
+        if area.area() == 0 {
+            return;
+        }
+        self.follow_output();
+        let index = |(x, y): (u16, u16)| {
+            let (x, y) = (x.min(area.width - 1), y.min(area.height - 1));
+            usize::from(y) * usize::from(area.width) + usize::from(x)
+        };
+        let (start, end) = (index(start), index(end));
+        if start > end {
+            return;
+        }
+        let width = usize::from(area.width);
+        let rows = Rect::new(
+            0,
+            (start / width) as u16,
+            area.width,
+            (end / width - start / width + 1) as u16,
+        );
+        if self
+            .selection
+            .is_some_and(|selection| selection.rows(area.width).intersects(rows))
+        {
+            self.select(None);
+        }
+        for cell in &mut self.buffer.content[start..=end] {
+            cell.reset();
+        }
+        self.detect_links();
+        let removed = self.images.remove(|_, image| {
+            (image.area.top()..image.area.bottom()).any(|y| {
+                let row = usize::from(y) * usize::from(area.width);
+                row + usize::from(image.area.left()) <= end
+                    && row + usize::from(image.area.right()) > start
+            })
+        });
+
+        if start == 0 && end == self.buffer.content.len() - 1 {
+            // Also covers the margin the grid leaves along the right and bottom edges.
+            self.repaint();
+            return;
+        }
+        for area in removed {
+            self.repaint_area(area);
+        }
+        if !self.ready() {
+            return;
+        }
+        let background = self.theme.background(Color::Reset);
+        let (first, last) = (self.buffer.pos_of(start), self.buffer.pos_of(end));
+        for y in first.1..=last.1 {
+            let left = if y == first.1 { first.0 } else { 0 };
+            let right = if y == last.1 { last.0 } else { area.width - 1 };
+            self.fill_cells(Rect::new(left, y, right - left + 1, 1), &background);
+        }
+        self.paint_cursor();
+    }
+
+    /// Scrolls the view `rows` rows back into the scrollback, or forward towards the live
+    /// screen if negative. Returns `true` if the view moved.
+    pub(crate) fn scroll_view(&mut self, rows: isize) -> bool {
+        let offset = self
+            .scrollback
+            .offset
+            .saturating_add_signed(rows)
+            .min(self.scrollback.len());
+        if offset == self.scrollback.offset {
+            return false;
+        }
+        self.scrollback.offset = offset;
+        self.selection = None;
+        self.repaint();
+        self.update_links(|_| {});
+        true
+    }
+
+    /// Returns the view to the live screen so new output is visible.
+    fn follow_output(&mut self) {
+        if self.scrollback.offset != 0 {
+            self.scrollback.offset = 0;
+            self.selection = None;
+            self.repaint();
+            self.update_links(|_| {});
+        }
+    }
+
+    /// Returns the cell at `client_x`, `client_y` in viewport coordinates, if it is on the
+    /// grid.
+    pub(crate) fn cell_at(&self, client_x: f64, client_y: f64) -> Option<(u16, u16)> {
+        let rect = self.canvas().get_bounding_client_rect();
+        let x = (client_x - rect.left()) / self.metrics.width;
+        let y = (client_y - rect.top()) / self.metrics.height;
+        let area = self.buffer.area;
+        (x >= 0.0 && y >= 0.0 && x < f64::from(area.width) && y < f64::from(area.height))
+            .then_some((x as u16, y as u16))
+    }
+
+    /// Returns the cell nearest to `client_x`, `client_y` in viewport coordinates, or `None`
+    /// if the grid is empty.
+    pub(crate) fn nearest_cell(&self, client_x: f64, client_y: f64) -> Option<(u16, u16)> {
+        let rect = self.canvas().get_bounding_client_rect();
+        let area = self.buffer.area;
+        if area.is_empty() {
+            return None;
+        }
+        let clamp = |position: f64, size: f64, cells: u16| {
+            ((position / size).max(0.0) as u16).min(cells - 1)
+        };
+        Some((
+            clamp(client_x - rect.left(), self.metrics.width, area.width),
+            clamp(client_y - rect.top(), self.metrics.height, area.height),
+        ))
+    }
+
+    /// Selects cells, or clears the selection, and repaints the rows that change.
+    pub(crate) fn select(&mut self, selection: Option<Selection>) {
+        let previous = std::mem::replace(&mut self.selection, selection);
+        if previous == selection {
+            return;
+        }
+        let width = self.buffer.area.width;
+        for selection in [previous, selection].into_iter().flatten() {
+            self.repaint_area(selection.rows(width));
+        }
+    }
+
+    /// Returns the selected text, one line per row with trailing spaces trimmed.
+    pub(crate) fn selected_text(&self) -> Option<String> {
+        Some(self.selection?.text(self))
+    }
+
+    /// Finds the URLs in the text on the screen again, if link detection is enabled.
+    pub(crate) fn detect_links(&mut self) {
+        self.links.detect(&self.buffer);
+        self.update_links(|_| {});
+    }
+
+    /// Applies `update` to the links, underlining the one under the mouse pointer and showing
+    /// a pointer cursor over it.
+    ///
+    /// Links only cover the live screen, so none is hovered while the view is scrolled back.
+    pub(crate) fn update_links(&mut self, update: impl FnOnce(&mut Links)) {
+        update(&mut self.links);
+        let hovered = match self.links.pointer {
+            Some((x, y)) if self.scrollback.offset == 0 => self.links.at(x, y).cloned(),
+            _ => None,
+        };
+        if hovered == self.links.hovered {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.links.hovered, hovered);
+        let cursor = if self.links.hovered.is_some() {
+            "pointer"
+        } else {
+            ""
+        };
+        let _ = self.canvas().style().set_property("cursor", cursor);
+
+        let area = self.buffer.area;
+        for link in previous.iter().chain(&self.links.hovered) {
+            for y in 0..area.height {
+                for x in (0..area.width).filter(|&x| link.area.contains(x, y)) {
+                    self.paint_cell_at(x, y);
+                }
+            }
+        }
+        self.paint_cursor();
+    }
+
+    /// Returns the cells shown in row `y` of the view, which may come from the scrollback.
+    pub(crate) fn visible_row(&self, y: u16) -> &[Cell] {
+        let width = usize::from(self.buffer.area.width);
+        let row = self.scrollback.len() - self.scrollback.offset + usize::from(y);
+        match row.checked_sub(self.scrollback.len()) {
+            None => self.scrollback.row(row),
+            Some(row) => &self.buffer.content[row * width..(row + 1) * width],
+        }
+    }
+
+    /// Moves blinking cells on to their next phase, repainting only the cells whose glyph
+    /// appears or disappears.
+    pub(crate) fn blink(&mut self) {
+        let previous = self.blink;
+        self.blink.advance();
+        if self.blink.reduced_motion || !self.ready() || !self.blinking.get() {
+            return;
+        }
+
+        let area = self.buffer.area;
+        let mut cursor_hit = false;
+        let mut blinking = false;
+        for y in 0..area.height {
+            for (x, span, cell) in spans(self.visible_row(y)) {
+                blinking |= cell.modifier.intersects(BLINKING);
+                if self.blink.hides(cell.modifier) != previous.hides(cell.modifier) {
+                    let painted = Rect::new(x, y, span, 1);
+                    self.paint_cell_content(x, y, span, cell);
+                    self.paint_images(painted);
+                    cursor_hit |= y == self.cursor.y && (x..x + span).contains(&self.cursor.x);
+                }
+            }
+        }
+        self.blinking.set(blinking);
+        if cursor_hit {
+            self.paint_cursor();
+        }
+    }
+
+    /// Stops or resumes blinking of cells and the cursor for `prefers-reduced-motion`.
+    pub(crate) fn set_reduced_motion(&mut self, reduced_motion: bool) {
+        self.blink.reduced_motion = reduced_motion;
+        self.cursor.blinked_off = false;
+        self.repaint();
+    }
+
+    /// Applies `update` to the cursor, erasing it from its old cell and painting it anew.
+    pub(crate) fn update_cursor(&mut self, update: impl FnOnce(&mut Cursor)) {
+        let previous = self.cursor;
+        update(&mut self.cursor);
+        if self.cursor == previous {
+            return;
+        }
+        if (previous.x, previous.y) != (self.cursor.x, self.cursor.y) {
+            // Keep a moving cursor visible rather than letting it vanish mid-blink.
+            self.cursor.blinked_off = false;
+            self.place_ime();
+            if !self.ime.preedit.is_empty() {
+                self.repaint_area(self.preedit_area(previous.x, previous.y));
+            }
+        }
+        self.paint_cell_at(previous.x, previous.y);
+        self.paint_cursor();
+    }
+
+    /// Returns `true` if `target` is the canvas or the IME text area, which take keyboard input
+    /// for the terminal.
+    pub(crate) fn owns_focus(&self, target: Option<EventTarget>) -> bool {
+        let Some(element) = target.and_then(|target| target.dyn_into::<HtmlElement>().ok()) else {
+            return false;
+        };
+        element == *self.canvas().unchecked_ref::<HtmlElement>() || self.ime.owns(&element)
+    }
+
+    /// Returns `true` if the canvas or the IME text area has keyboard focus.
+    pub(crate) fn has_focus(&self) -> bool {
+        let active = window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.active_element());
+        self.owns_focus(active.map(Into::into))
+    }
+
+    /// Draws the cursor solid or hollow as the canvas gains or loses focus, and reports the
+    /// change.
+    pub(crate) fn set_focused(&mut self, focused: bool) {
+        if self.cursor.focused == focused {
+            return;
+        }
+        self.update_cursor(|cursor| cursor.focused = focused);
+        self.on_event.emit(if focused {
+            Event::FocusGained
+        } else {
+            Event::FocusLost
+        });
+    }
+
+    /// Moves the hidden IME text area to the cursor.
+    pub(crate) fn place_ime(&self) {
+        let rect = self.canvas().get_bounding_client_rect();
+        let (x, y) = self.metrics.origin(self.cursor.x, self.cursor.y);
+        self.ime
+            .follow(rect.left() + x, rect.top() + y, self.metrics.height);
+    }
+
+    /// Shows `text` being composed in an IME at the cursor, or stops showing it if empty.
+    pub(crate) fn set_preedit(&mut self, text: String) {
+        let previous = self.preedit_area(self.cursor.x, self.cursor.y);
+        self.ime.preedit = text;
+        let area = self.preedit_area(self.cursor.x, self.cursor.y);
+        self.repaint_area(if area.width > previous.width {
+            area
+        } else {
+            previous
+        });
+    }
+
+    /// Returns the cells the text being composed covers with the cursor at (`x`, `y`).
+    fn preedit_area(&self, x: u16, y: u16) -> Rect {
+        let width = ime::graphemes(&self.ime.preedit)
+            .iter()
+            .map(|(_, width)| width)
+            .sum::<u16>()
+            .max(1);
+        let area = Rect::new(x, y, width, 1);
+        if area.intersects(self.buffer.area) {
+            area.intersection(self.buffer.area)
+        } else {
+            Rect::default()
+        }
+    }
+
+    /// Paints the background over the whole canvas and every stored cell on top.
+    pub(crate) fn repaint(&self) {
+        if !self.ready() {
+            return;
+        }
+        let canvas = self.canvas();
+        self.context
+            .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+            .unwrap();
+        self.context
+            .set_fill_style_str(&self.theme.background(Color::Reset));
+        self.context.fill_rect(
+            0.0,
+            0.0,
+            f64::from(canvas.width()),
+            f64::from(canvas.height()),
+        );
+        self.reset_transform();
+        let area = self.buffer.area;
+        for y in 0..area.height {
+            self.paint_row(y, 0, area.width);
+        }
+        self.paint_images(area);
+        self.paint_cursor();
+        self.paint_scrollback_indicator();
+    }
+
+    /// Paints the cells of `area` of the live screen and the images over them.
+    pub(crate) fn repaint_area(&self, area: Rect) {
+        if self.scrollback.offset != 0 {
+            return self.repaint();
+        }
+        if !self.ready() {
+            return;
+        }
+        let area = area.intersection(self.buffer.area);
+        let mut painted = area;
+        for y in area.top()..area.bottom() {
+            painted = painted.union(self.paint_row(y, area.left(), area.right()));
+        }
+        self.paint_images(painted);
+        self.paint_cursor();
+    }
+
+    /// Removes the images `remove` returns `true` for, given their id, and repaints the cells
+    /// under them.
+    pub(crate) fn remove_images(&mut self, remove: impl FnMut(usize, &Image) -> bool) {
+        for area in self.images.remove(remove) {
+            self.repaint_area(area);
+        }
+    }
+
+    /// Draws the part of each image that lies within the cells of `clip`.
+    fn paint_images(&self, clip: Rect) {
+        let area = self.buffer.area;
+        for image in self.images.iter() {
+            // Images sit on the live screen, so they move down as the view scrolls back.
+            let Ok(top) = u16::try_from(usize::from(image.area.y) + self.scrollback.offset) else {
+                continue;
+            };
+            let view = Rect {
+                y: top,
+                ..image.area
+            };
+            if !view.intersects(clip) || !view.intersects(area) {
+                continue;
+            }
+            let Some((x, y, width, height)) = image.destination(self.device_rect(view)) else {
+                continue;
+            };
+            let (left, top, right, bottom) =
+                self.device_rect(view.intersection(clip).intersection(area));
+
+            self.context.save();
+            self.context
+                .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+                .unwrap();
+            self.context.begin_path();
+            self.context.rect(left, top, right - left, bottom - top);
+            self.context.clip();
+            image.draw(&self.context, x, y, width, height);
+            self.context.restore();
+        }
+    }
+
+    /// Paints the cell shown at (`x`, `y`), if any, or the wide grapheme covering it.
+    pub(crate) fn paint_cell_at(&self, x: u16, y: u16) {
+        let area = self.buffer.area;
+        if self.ready() && x < area.right() && y < area.bottom() {
+            let painted = self.paint_row(y, x, x + 1);
+            self.paint_images(painted);
+        }
+    }
+
+    /// Paints the cells of row `y` of the view from column `left` up to `right`, along with
+    /// wide graphemes reaching into them, and returns the cells painted.
+    ///
+    /// The blank cells a wide grapheme covers are left out, as painting their backgrounds
+    /// would cover the grapheme's right half.
+    fn paint_row(&self, y: u16, left: u16, right: u16) -> Rect {
+        let mut painted = Rect::new(left, y, right.saturating_sub(left), 1);
+        for (x, span, cell) in spans(self.visible_row(y)) {
+            if x < right && x + span > left {
+                self.paint_cell_content(x, y, span, cell);
+                painted = painted.union(Rect::new(x, y, span, 1));
+            }
+        }
+        painted
+    }
+
+    /// Paints how far the view is scrolled back into the top right corner, if it is.
+    fn paint_scrollback_indicator(&self) {
+        if self.scrollback.offset == 0 {
+            return;
+        }
+        let label = format!(
+            " \u{2191} {}/{} ",
+            self.scrollback.offset,
+            self.scrollback.len()
+        );
+        let width = label.chars().count() as u16;
+        let area = self.buffer.area;
+        if width > area.width || area.height == 0 {
+            return;
+        }
+
+        let left = area.width - width;
+        let foreground = self.theme.background(Color::Reset);
+        self.fill_cells(
+            Rect::new(left, 0, width, 1),
+            &self.theme.foreground(Color::Reset),
+        );
+        for (x, c) in (left..).zip(label.chars()) {
+            self.paint_glyph(
+                x,
+                0,
+                c.encode_utf8(&mut [0; 4]),
+                &foreground,
+                Modifier::empty(),
+            );
+        }
+    }
+
+    /// Paints `cell` at (`x`, `y`) across the `span` cells its grapheme takes.
+    fn paint_cell_content(&self, x: u16, y: u16, span: u16, cell: &Cell) {
+        let (foreground, mut background) = if cell.modifier.contains(Modifier::REVERSED) {
+            (
+                self.theme.background(cell.bg),
+                self.theme.foreground(cell.fg),
+            )
+        } else {
+            (
+                self.theme.foreground(cell.fg),
+                self.theme.background(cell.bg),
+            )
+        };
+        let width = self.buffer.area.width;
+        if self
+            .selection
+            .is_some_and(|selection| selection.contains(x, y, width))
+        {
+            background = self.theme.background(self.theme.selection);
+        }
+        self.fill_cells(Rect::new(x, y, span, 1), &background);
+        if cell.modifier.intersects(BLINKING) {
+            self.blinking.set(true);
+        }
+        if !self.blink.hides(cell.modifier) && !cell.modifier.contains(Modifier::HIDDEN) {
+            self.paint_glyph(x, y, &cell.symbol, &foreground, cell.modifier);
+        }
+        let hovered = self.scrollback.offset == 0 && self.links.hovers(x, y);
+        for column in x..x + span {
+            if hovered || cell.modifier.contains(Modifier::UNDERLINED) {
+                self.paint_line(column, y, &foreground, 1.0);
+            }
+            if cell.modifier.contains(Modifier::CROSSED_OUT) {
+                self.paint_line(column, y, &foreground, 0.5);
+            }
+        }
+    }
+
+    /// Paints a horizontal line across the cell at (`x`, `y`), `position` of the way down from
+    /// its top, e.g. `1.0` for an underline.
+    fn paint_line(&self, x: u16, y: u16, color: &str, position: f64) {
+        let (left, top, right, bottom) = self.device_rect(Rect::new(x, y, 1, 1));
+        let line = (self.scale * (self.metrics.height / 16.0)).round().max(1.0);
+        let line_top = (top + (bottom - top) * position - line).round().max(top);
+        self.context
+            .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+            .unwrap();
+        self.context.set_fill_style_str(color);
+        self.context.fill_rect(left, line_top, right - left, line);
+        self.reset_transform();
+    }
+
+    /// Paints `grapheme` into the cell at (`x`, `y`) in `color`, bold, italic or faint as
+    /// `modifier` asks.
+    fn paint_glyph(&self, x: u16, y: u16, grapheme: &str, color: &str, modifier: Modifier) {
+        let metrics = self.metrics;
+        let (px, py) = metrics.origin(x, y);
+        let style = match (
+            modifier.contains(Modifier::ITALIC),
+            modifier.contains(Modifier::BOLD),
+        ) {
+            (false, false) => "",
+            (false, true) => "bold ",
+            (true, false) => "italic ",
+            (true, true) => "italic bold ",
+        };
+
+        self.context.set_fill_style_str(color);
+        if modifier.contains(Modifier::DIM) {
+            self.context.set_global_alpha(0.5);
+        }
+        match self.glyphs.fallback(&self.font, grapheme) {
+            None => {
+                self.context
+                    .set_font(&format!("{style}{}", self.font.css()));
+                self.context
+                    .fill_text(grapheme, px + metrics.glyph_offset, py + metrics.baseline)
+                    .unwrap();
+            }
+            Some(family) => self.paint_fallback(grapheme, family, style, px, py),
+        }
+        self.context.set_global_alpha(1.0);
+    }
+
+    /// Paints a glyph from a fallback `family`, scaled down to fit the cells it spans and
+    /// centred in them, since its advance and baseline need not match the primary font.
+    fn paint_fallback(&self, grapheme: &str, family: &str, style: &str, px: f64, py: f64) {
+        let metrics = self.metrics;
+        let cells = grapheme.width().max(1) as f64;
+
+        self.context.set_font(&format!(
+            "{style}{}",
+            self.font.css_with(family, "monospace")
+        ));
+        let Ok(glyph) = self.context.measure_text(grapheme) else {
+            return;
+        };
+        let ascent = glyph.actual_bounding_box_ascent();
+        let descent = glyph.actual_bounding_box_descent();
+        let scale = (metrics.width * cells / glyph.width())
+            .min(metrics.height / (ascent + descent))
+            .min(1.0);
+
+        self.context.save();
+        self.context
+            .translate(px + metrics.width * cells / 2.0, py + metrics.height / 2.0)
+            .unwrap();
+        if scale.is_finite() && scale > 0.0 {
+            self.context.scale(scale, scale).unwrap();
+        }
+        self.context.set_text_align("center");
+        self.context
+            .fill_text(grapheme, 0.0, (ascent - descent) / 2.0)
+            .unwrap();
+        self.context.restore();
+    }
+
+    /// Paints the text being composed in an IME over the cells from the cursor on, underlined
+    /// like native text fields show it. It takes the place of the cursor.
+    fn paint_preedit(&self) {
+        let Cursor { mut x, y, .. } = self.cursor;
+        let foreground = self.theme.foreground(Color::Reset);
+        let background = self.theme.background(Color::Reset);
+        for (grapheme, width) in ime::graphemes(&self.ime.preedit) {
+            if x + width > self.buffer.area.right() {
+                break;
+            }
+            self.fill_cells(Rect::new(x, y, width, 1), &background);
+            self.paint_glyph(x, y, &grapheme, &foreground, Modifier::empty());
+            for column in x..x + width {
+                self.paint_line(column, y, &foreground, 1.0);
+            }
+            x += width;
+        }
+    }
+
+    /// Paints the cursor over the cell it is on.
+    pub(crate) fn paint_cursor(&self) {
+        let Cursor { x, y, .. } = self.cursor;
+        let area = self.buffer.area;
+        if !self.ready() || self.scrollback.offset != 0 || x >= area.right() || y >= area.bottom() {
+            return;
+        }
+        if !self.ime.preedit.is_empty() {
+            return self.paint_preedit();
+        }
+        if !self.cursor.drawn() {
+            return;
+        }
+
+        let color = self.theme.foreground(self.theme.cursor);
+        let (left, top, right, bottom) = self.device_rect(Rect::new(x, y, 1, 1));
+        let line = (self.scale * (self.metrics.height / 12.0)).round().max(1.0);
+
+        self.context
+            .set_transform(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+            .unwrap();
+        self.context.set_fill_style_str(&color);
+        match (self.cursor.focused, self.cursor.shape) {
+            (false, _) => {
+                let edge = self.scale.round().max(1.0);
+                self.context.fill_rect(left, top, right - left, edge);
+                self.context
+                    .fill_rect(left, bottom - edge, right - left, edge);
+                self.context.fill_rect(left, top, edge, bottom - top);
+                self.context
+                    .fill_rect(right - edge, top, edge, bottom - top);
+            }
+            (true, CursorShape::Block) => {
+                self.context
+                    .fill_rect(left, top, right - left, bottom - top);
+            }
+            (true, CursorShape::Underline) => {
+                self.context
+                    .fill_rect(left, bottom - line, right - left, line);
+            }
+            (true, CursorShape::Bar) => {
+                self.context.fill_rect(left, top, line, bottom - top);
+            }
+        }
+        self.reset_transform();
+
+        if self.cursor.focused && self.cursor.shape == CursorShape::Block {
+            let cell = self.buffer.get(x, y);
+            self.paint_glyph(
+                x,
+                y,
+                &cell.symbol,
+                &self.theme.background(cell.bg),
+                cell.modifier,
+            );
+        }
+    }
+}
+
+/// Returns the cells of `row` that are painted, as (column, columns spanned, cell), leaving out
+/// the cells covered by a wide grapheme before them.
+///
+/// ratatui blanks the cells a wide grapheme covers rather than emptying them, so they can only
+/// be told apart by their position.
+pub(crate) fn spans(row: &[Cell]) -> impl Iterator<Item = (u16, u16, &Cell)> {
+    let mut x = 0;
+    std::iter::from_fn(move || {
+        let cell = row.get(x)?;
+        let start = x;
+        x += cell.symbol.width().clamp(1, row.len() - x);
+        Some((start as u16, (x - start) as u16, cell))
+    })
+}
+
+/// Returns a copy of `buffer` cut or padded to `area`, with every cell kept at its position.
+fn resized(buffer: &Buffer, area: Rect) -> Buffer {
+    let mut resized = Buffer::empty(area);
+    for y in 0..area.height.min(buffer.area.height) {
+        for x in 0..area.width.min(buffer.area.width) {
+            *resized.get_mut(x, y) = buffer.get(x, y).clone();
+        }
+    }
+    resized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns(line: &str) -> Vec<(u16, u16, String)> {
+        let buffer = Buffer::with_lines(vec![line]);
+        spans(&buffer.content)
+            .map(|(x, span, cell)| (x, span, cell.symbol.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn spans_leave_out_cells_covered_by_wide_graphemes() {
+        assert_eq!(
+            columns("a\u{6f22}b"),
+            [
+                (0, 1, "a".to_string()),
+                (1, 2, "\u{6f22}".to_string()),
+                (3, 1, "b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn spans_stop_at_the_end_of_the_row() {
+        let mut row = Buffer::with_lines(vec!["ab"]).content;
+        row[1].set_symbol("\u{6f22}");
+        let columns: Vec<_> = spans(&row).map(|(x, span, _)| (x, span)).collect();
+        assert_eq!(columns, [(0, 1), (1, 1)]);
+        assert_eq!(spans(&[]).count(), 0);
+    }
+}
@@ -0,0 +1,210 @@
+//! Rows scrolled off the top of the screen and browsing back through them.
+
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use ratatui::buffer::{Buffer, Cell};
+use web_sys::{window, KeyboardEvent, WheelEvent};
+
+use crate::{dom::Listener, screen::Screen};
+
+/// The number of rows kept by default.
+pub const DEFAULT_SCROLLBACK: usize = 1000;
+
+/// A bounded history of the rows scrolled off the top of the screen, oldest first.
+#[derive(Debug)]
+pub(crate) struct Scrollback {
+    rows: VecDeque<Vec<Cell>>,
+    limit: usize,
+    /// How many rows the view is scrolled back from the live screen.
+    pub(crate) offset: usize,
+}
+
+impl Default for Scrollback {
+    fn default() -> Self {
+        Scrollback {
+            rows: VecDeque::new(),
+            limit: DEFAULT_SCROLLBACK,
+            offset: 0,
+        }
+    }
+}
+
+impl Scrollback {
+    /// Returns the number of rows kept.
+    pub(crate) fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns the row `i` rows after the oldest one.
+    pub(crate) fn row(&self, i: usize) -> &[Cell] {
+        &self.rows[i]
+    }
+
+    /// Appends `row`, dropping the oldest row once the limit is reached.
+    pub(crate) fn push(&mut self, row: Vec<Cell>) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.rows.len() == self.limit {
+            self.rows.pop_front();
+        }
+        self.rows.push_back(row);
+    }
+
+    /// Moves the top `n` rows of `buffer` into the scrollback, shifting the other rows up and
+    /// clearing the rows that come in at the bottom.
+    pub(crate) fn scroll_in(&mut self, buffer: &mut Buffer, n: u16) {
+        let area = buffer.area;
+        let width = usize::from(area.width);
+        if width == 0 {
+            return;
+        }
+        let n = usize::from(n.min(area.height));
+        let rows: Vec<Cell> = buffer.content.drain(..n * width).collect();
+        for row in rows.chunks(width) {
+            self.push(row.to_vec());
+        }
+        buffer.content.resize(area.area() as usize, Cell::default());
+    }
+
+    /// Keeps at most `limit` rows, dropping the oldest ones.
+    pub(crate) fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+        let excess = self.rows.len().saturating_sub(limit);
+        self.rows.drain(..excess);
+        self.offset = self.offset.min(self.rows.len());
+    }
+}
+
+/// Scrolls the view of `screen` through its history with the mouse wheel on the canvas and
+/// Shift+PageUp / Shift+PageDown while the canvas has focus.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let document = window().unwrap().document().unwrap();
+    let canvas = screen.borrow().canvas();
+
+    let wheel = {
+        let screen = Rc::clone(screen);
+        Listener::active(&canvas, "wheel", move |event: WheelEvent| {
+            // Ctrl+wheel zooms instead.
+            if event.ctrl_key() || event.delta_y() == 0.0 {
+                return;
+            }
+            let mut screen = screen.borrow_mut();
+            // The application gets the wheel while it captures the mouse.
+            if screen.mouse_capture {
+                return;
+            }
+            let rows = match event.delta_mode() {
+                WheelEvent::DOM_DELTA_PIXEL => event.delta_y() / screen.metrics.height,
+                WheelEvent::DOM_DELTA_LINE => event.delta_y(),
+                _ => event.delta_y() * f64::from(screen.buffer.area.height),
+            };
+            let rows = (rows.abs().round() as isize).max(1);
+            let rows = if event.delta_y() < 0.0 { rows } else { -rows };
+            if screen.scroll_view(rows) {
+                event.prevent_default();
+            }
+        })
+    };
+
+    let keys = {
+        let screen = Rc::clone(screen);
+        Listener::active(&document, "keydown", move |event: KeyboardEvent| {
+            if !event.shift_key() {
+                return;
+            }
+            let mut screen = screen.borrow_mut();
+            if !screen.has_focus() {
+                return;
+            }
+            let page = screen.buffer.area.height.saturating_sub(1).max(1) as isize;
+            let rows = match event.key().as_str() {
+                "PageUp" => page,
+                "PageDown" => -page,
+                _ => return,
+            };
+            if screen.scroll_view(rows) {
+                event.prevent_default();
+            }
+        })
+    };
+
+    vec![wheel, keys]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(symbol: &str) -> Vec<Cell> {
+        let mut cell = Cell::default();
+        cell.set_symbol(symbol);
+        vec![cell]
+    }
+
+    fn symbols(scrollback: &Scrollback) -> Vec<&str> {
+        (0..scrollback.len())
+            .map(|i| scrollback.row(i)[0].symbol.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn keeps_rows_oldest_first() {
+        let mut scrollback = Scrollback::default();
+        for symbol in ["a", "b", "c"] {
+            scrollback.push(row(symbol));
+        }
+        assert_eq!(symbols(&scrollback), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn drops_the_oldest_rows_at_the_limit() {
+        let mut scrollback = Scrollback::default();
+        scrollback.set_limit(2);
+        for symbol in ["a", "b", "c"] {
+            scrollback.push(row(symbol));
+        }
+        assert_eq!(symbols(&scrollback), ["b", "c"]);
+    }
+
+    #[test]
+    fn lowering_the_limit_drops_rows_and_clamps_the_offset() {
+        let mut scrollback = Scrollback::default();
+        for symbol in ["a", "b", "c", "d"] {
+            scrollback.push(row(symbol));
+        }
+        scrollback.offset = 4;
+        scrollback.set_limit(1);
+        assert_eq!(symbols(&scrollback), ["d"]);
+        assert_eq!(scrollback.offset, 1);
+    }
+
+    #[test]
+    fn scrolls_rows_of_a_buffer_in() {
+        let mut scrollback = Scrollback::default();
+        let mut buffer = Buffer::with_lines(vec!["a", "b", "c"]);
+        scrollback.scroll_in(&mut buffer, 2);
+        assert_eq!(symbols(&scrollback), ["a", "b"]);
+        assert_eq!(buffer, Buffer::with_lines(vec!["c", " ", " "]));
+
+        scrollback.scroll_in(&mut buffer, 9);
+        assert_eq!(symbols(&scrollback), ["a", "b", "c", " ", " "]);
+    }
+
+    #[test]
+    fn scrolls_nothing_in_from_a_buffer_without_columns() {
+        let mut scrollback = Scrollback::default();
+        let mut buffer = Buffer::empty(ratatui::prelude::Rect::new(0, 0, 0, 3));
+        scrollback.scroll_in(&mut buffer, 1);
+        assert_eq!(scrollback.len(), 0);
+        assert_eq!(buffer.area.height, 3);
+    }
+
+    #[test]
+    fn keeps_nothing_without_a_limit() {
+        let mut scrollback = Scrollback::default();
+        scrollback.set_limit(0);
+        scrollback.push(row("a"));
+        assert_eq!(scrollback.len(), 0);
+    }
+}
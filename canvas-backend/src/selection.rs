@@ -0,0 +1,336 @@
+//! Selecting text on the canvas with the mouse, and copying it as plain text.
+
+use std::{cell::RefCell, rc::Rc};
+
+use ratatui::{buffer::Cell, prelude::Rect};
+use web_sys::{window, ClipboardEvent, MouseEvent};
+
+use crate::{
+    dom::Listener,
+    screen::{spans, Screen},
+};
+
+/// Characters that belong to a word besides letters and digits, so a double-click selects a
+/// whole path or URL.
+const WORD_CHARS: &str = "_-.~/:@#%+=?&";
+
+/// What a selection grows by as it is dragged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Unit {
+    Cell,
+    Word,
+    Line,
+}
+
+/// The cells selected on the view, between the cell a drag started on and the one it is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Selection {
+    /// The first selected cell in reading order, or the top left corner of a block.
+    start: (u16, u16),
+    /// The last selected cell in reading order, or the bottom right corner of a block.
+    end: (u16, u16),
+    /// Whether the selection is a rectangle rather than running from line to line.
+    block: bool,
+}
+
+impl Selection {
+    /// Selects from `anchor` to `head` on `screen`, grown to whole words or lines by `unit`.
+    pub(crate) fn new(
+        screen: &Screen,
+        anchor: (u16, u16),
+        head: (u16, u16),
+        unit: Unit,
+        block: bool,
+    ) -> Selection {
+        if block {
+            return Selection {
+                start: (anchor.0.min(head.0), anchor.1.min(head.1)),
+                end: (anchor.0.max(head.0), anchor.1.max(head.1)),
+                block,
+            };
+        }
+        let (mut start, mut end) = if (anchor.1, anchor.0) <= (head.1, head.0) {
+            (anchor, head)
+        } else {
+            (head, anchor)
+        };
+        match unit {
+            Unit::Cell => {}
+            Unit::Word => {
+                start.0 = word_bounds(screen.visible_row(start.1), start.0).0;
+                end.0 = word_bounds(screen.visible_row(end.1), end.0).1;
+            }
+            Unit::Line => {
+                start.0 = 0;
+                end.0 = screen.buffer.area.width.saturating_sub(1);
+            }
+        }
+        Selection { start, end, block }
+    }
+
+    /// Returns the first and last selected column of row `y`, if any of it is selected.
+    fn columns(&self, y: u16, width: u16) -> Option<(u16, u16)> {
+        if y < self.start.1 || y > self.end.1 {
+            return None;
+        }
+        if self.block {
+            return Some((self.start.0, self.end.0));
+        }
+        let first = if y == self.start.1 { self.start.0 } else { 0 };
+        let last = if y == self.end.1 {
+            self.end.0
+        } else {
+            width.saturating_sub(1)
+        };
+        Some((first, last))
+    }
+
+    pub(crate) fn contains(&self, x: u16, y: u16, width: u16) -> bool {
+        self.columns(y, width)
+            .is_some_and(|(first, last)| (first..=last).contains(&x))
+    }
+
+    /// Returns the rows the selection spans, across the whole width.
+    pub(crate) fn rows(&self, width: u16) -> Rect {
+        Rect::new(0, self.start.1, width, self.end.1 - self.start.1 + 1)
+    }
+
+    /// Returns the selected text of `screen`, one line per row with trailing spaces trimmed.
+    pub(crate) fn text(&self, screen: &Screen) -> String {
+        self.text_of(screen.buffer.area.width, |y| screen.visible_row(y))
+    }
+
+    /// Returns the selected text of the rows of a view `width` cells wide, of which `row`
+    /// returns the cells.
+    fn text_of<'a>(&self, width: u16, row: impl Fn(u16) -> &'a [Cell]) -> String {
+        let mut lines = Vec::new();
+        for y in self.start.1..=self.end.1 {
+            let Some((first, last)) = self.columns(y, width) else {
+                continue;
+            };
+            let mut line = String::new();
+            for (x, _, cell) in spans(row(y)) {
+                if (first..=last).contains(&x) {
+                    line.push_str(&cell.symbol);
+                }
+            }
+            lines.push(line.trim_end_matches(' ').to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+/// Returns which kind of text `symbol` is, so a word is a run of graphemes of the same kind.
+fn class(symbol: &str) -> u8 {
+    match symbol.chars().next().unwrap_or(' ') {
+        c if c.is_whitespace() => 0,
+        c if c.is_alphanumeric() || WORD_CHARS.contains(c) => 1,
+        _ => 2,
+    }
+}
+
+/// Returns the first and last column of the word around column `x` of `row`.
+///
+/// Wide graphemes count with both their cells, rather than the blank cell they cover
+/// splitting the word.
+fn word_bounds(row: &[Cell], x: u16) -> (u16, u16) {
+    let graphemes: Vec<(u16, u16, u8)> = spans(row)
+        .map(|(x, span, cell)| (x, span, class(&cell.symbol)))
+        .collect();
+    let Some(i) = graphemes
+        .iter()
+        .position(|&(start, span, _)| x < start + span)
+    else {
+        return (x, x);
+    };
+    let target = graphemes[i].2;
+    let word = |grapheme: &&(u16, u16, u8)| grapheme.2 == target;
+    let first = graphemes[..=i].iter().rev().take_while(word).last();
+    let last = graphemes[i..].iter().take_while(word).last();
+    match (first, last) {
+        (Some(&(first, _, _)), Some(&(start, span, _))) => (first, start + span - 1),
+        _ => (x, x),
+    }
+}
+
+/// A drag selecting cells.
+#[derive(Debug)]
+struct Drag {
+    anchor: (u16, u16),
+    unit: Unit,
+    block: bool,
+}
+
+/// Selects text with the left mouse button: a drag selects from line to line, an Alt-drag
+/// selects a rectangle, a double-click a word and a triple-click a line. Copying while the
+/// canvas has focus, e.g. with Ctrl+C, copies the selection as plain text.
+///
+/// While the mouse is captured by the application, Shift has to be held down to select.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let window = window().unwrap();
+    let document = window.document().unwrap();
+    let canvas = screen.borrow().canvas();
+    let drag = Rc::new(RefCell::new(None::<Drag>));
+
+    let down = {
+        let screen = Rc::clone(screen);
+        let drag = Rc::clone(&drag);
+        Listener::new(&canvas, "mousedown", move |event: MouseEvent| {
+            if event.button() != 0 {
+                return;
+            }
+            let mut screen = screen.borrow_mut();
+            if screen.mouse_capture && !event.shift_key() {
+                return;
+            }
+            let Some(anchor) = screen.cell_at(event.client_x().into(), event.client_y().into())
+            else {
+                return;
+            };
+            let unit = match event.detail() {
+                2 => Unit::Word,
+                n if n >= 3 => Unit::Line,
+                _ => Unit::Cell,
+            };
+            let block = event.alt_key();
+            let selection =
+                (unit != Unit::Cell).then(|| Selection::new(&screen, anchor, anchor, unit, block));
+            screen.select(selection);
+            *drag.borrow_mut() = Some(Drag {
+                anchor,
+                unit,
+                block,
+            });
+        })
+    };
+
+    let moved = {
+        let screen = Rc::clone(screen);
+        let drag = Rc::clone(&drag);
+        Listener::new(&document, "mousemove", move |event: MouseEvent| {
+            let drag = drag.borrow();
+            let Some(drag) = drag.as_ref() else {
+                return;
+            };
+            let mut screen = screen.borrow_mut();
+            let (x, y) = (event.client_x().into(), event.client_y().into());
+            let Some(head) = screen.nearest_cell(x, y) else {
+                return;
+            };
+            // A click without a drag selects nothing.
+            if drag.unit == Unit::Cell && head == drag.anchor && screen.selection.is_none() {
+                return;
+            }
+            let selection = Selection::new(&screen, drag.anchor, head, drag.unit, drag.block);
+            screen.select(Some(selection));
+        })
+    };
+
+    let up = {
+        let drag = Rc::clone(&drag);
+        Listener::new(&document, "mouseup", move |event: MouseEvent| {
+            if event.button() == 0 {
+                drag.borrow_mut().take();
+            }
+        })
+    };
+
+    let copy = {
+        let screen = Rc::clone(screen);
+        Listener::active(&document, "copy", move |event: ClipboardEvent| {
+            let mut screen = screen.borrow_mut();
+            if !screen.has_focus() {
+                return;
+            }
+            let (Some(text), Some(data)) = (screen.selected_text(), event.clipboard_data()) else {
+                return;
+            };
+            if data.set_data("text/plain", &text).is_ok() {
+                event.prevent_default();
+                screen.select(None);
+            }
+        })
+    };
+
+    vec![down, moved, up, copy]
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+
+    use super::*;
+
+    fn text(lines: Vec<&str>, start: (u16, u16), end: (u16, u16), block: bool) -> String {
+        let buffer = Buffer::with_lines(lines);
+        let width = buffer.area.width;
+        let selection = Selection { start, end, block };
+        selection.text_of(width, |y| {
+            let row = usize::from(y) * usize::from(width);
+            &buffer.content[row..row + usize::from(width)]
+        })
+    }
+
+    fn word_at(line: &str, x: u16) -> (u16, u16) {
+        word_bounds(&Buffer::with_lines(vec![line]).content, x)
+    }
+
+    #[test]
+    fn text_runs_from_line_to_line() {
+        let lines = vec!["first line  ", "second line ", "third line  "];
+        assert_eq!(
+            text(lines.clone(), (6, 0), (5, 2), false),
+            "line\nsecond line\nthird"
+        );
+        assert_eq!(text(lines, (0, 1), (5, 1), false), "second");
+    }
+
+    #[test]
+    fn text_of_a_block() {
+        let lines = vec!["abcdef", "ghijkl", "mnopqr"];
+        assert_eq!(text(lines, (1, 0), (3, 2), true), "bcd\nhij\nnop");
+    }
+
+    #[test]
+    fn text_trims_trailing_spaces_only() {
+        let lines = vec!["  indented   ", "             "];
+        assert_eq!(text(lines, (0, 0), (12, 1), false), "  indented\n");
+    }
+
+    #[test]
+    fn text_takes_wide_characters_once() {
+        let lines = vec!["a\u{6f22}\u{5b57}b"];
+        assert_eq!(
+            text(lines.clone(), (0, 0), (5, 0), false),
+            "a\u{6f22}\u{5b57}b"
+        );
+        assert_eq!(text(lines, (1, 0), (2, 0), false), "\u{6f22}");
+    }
+
+    #[test]
+    fn words_include_path_and_url_characters() {
+        let line = "open https://example.com/a?b=c now";
+        assert_eq!(word_at(line, 10), (5, 29));
+        assert_eq!(word_at(line, 0), (0, 3));
+        assert_eq!(word_at(line, 33), (31, 33));
+    }
+
+    #[test]
+    fn words_span_wide_characters() {
+        let line = "ab \u{6f22}\u{5b57}\u{304b}\u{306a} cd";
+        assert_eq!(word_at(line, 3), (3, 10));
+        assert_eq!(word_at(line, 6), (3, 10));
+        assert_eq!(word_at(line, 10), (3, 10));
+        assert_eq!(word_at(line, 12), (12, 13));
+        assert_eq!(word_at(line, 11), (11, 11));
+    }
+
+    #[test]
+    fn words_are_runs_of_the_same_kind() {
+        let line = "foo(bar)  baz";
+        assert_eq!(word_at(line, 1), (0, 2));
+        assert_eq!(word_at(line, 3), (3, 3));
+        assert_eq!(word_at(line, 8), (8, 9));
+        assert_eq!(word_at(line, 5), (4, 6));
+    }
+}
@@ -0,0 +1,142 @@
+//! The colours cells fall back to.
+
+use ratatui::style::Color;
+
+/// The colours used where cells leave them to the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    /// The colour of text whose foreground is [`Color::Reset`].
+    pub foreground: Color,
+    /// The colour of cells whose background is [`Color::Reset`] and of cleared regions.
+    pub background: Color,
+    /// The colour of the cursor.
+    pub cursor: Color,
+    /// The background colour of selected cells.
+    pub selection: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            foreground: Color::White,
+            background: Color::Rgb(0x26, 0x32, 0x38),
+            cursor: Color::White,
+            selection: Color::Rgb(0x54, 0x6e, 0x7a),
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the CSS colour of a cell foreground `color`.
+    pub(crate) fn foreground(&self, color: Color) -> String {
+        css_color(color, self.foreground)
+    }
+
+    /// Returns the CSS colour of a cell background `color`.
+    pub(crate) fn background(&self, color: Color) -> String {
+        css_color(color, self.background)
+    }
+}
+
+/// The 16 ANSI colours in the order of their indices, as xterm renders them.
+const ANSI: [(u8, u8, u8); 16] = [
+    (0x00, 0x00, 0x00),
+    (0xcd, 0x00, 0x00),
+    (0x00, 0xcd, 0x00),
+    (0xcd, 0xcd, 0x00),
+    (0x00, 0x00, 0xee),
+    (0xcd, 0x00, 0xcd),
+    (0x00, 0xcd, 0xcd),
+    (0xe5, 0xe5, 0xe5),
+    (0x7f, 0x7f, 0x7f),
+    (0xff, 0x00, 0x00),
+    (0x00, 0xff, 0x00),
+    (0xff, 0xff, 0x00),
+    (0x5c, 0x5c, 0xff),
+    (0xff, 0x00, 0xff),
+    (0x00, 0xff, 0xff),
+    (0xff, 0xff, 0xff),
+];
+
+/// Converts `color` to a CSS colour, using `default` for [`Color::Reset`].
+///
+/// Named and indexed colours follow the xterm palette, since most of their names are not CSS
+/// colours.
+fn css_color(color: Color, default: Color) -> String {
+    let index = match color {
+        Color::Reset if default == Color::Reset => return css_color(Color::White, Color::White),
+        Color::Reset => return css_color(default, Color::Reset),
+        Color::Rgb(r, g, b) => return format!("#{r:02x}{g:02x}{b:02x}"),
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Indexed(i) => i,
+    };
+    let (r, g, b) = match index {
+        0..=15 => ANSI[usize::from(index)],
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = index - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        _ => {
+            let v = 8 + (index - 232) * 10;
+            (v, v, v)
+        }
+    };
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rgb_colors_are_hex() {
+        assert_eq!(
+            css_color(Color::Rgb(0x12, 0xab, 0x00), Color::Reset),
+            "#12ab00"
+        );
+    }
+
+    #[test]
+    fn named_colors_follow_xterm() {
+        assert_eq!(css_color(Color::Red, Color::Reset), "#cd0000");
+        assert_eq!(css_color(Color::LightBlue, Color::Reset), "#5c5cff");
+        assert_eq!(css_color(Color::Indexed(1), Color::Reset), "#cd0000");
+    }
+
+    #[test]
+    fn indexed_colors_cover_the_cube_and_the_grays() {
+        assert_eq!(css_color(Color::Indexed(16), Color::Reset), "#000000");
+        assert_eq!(css_color(Color::Indexed(196), Color::Reset), "#ff0000");
+        assert_eq!(css_color(Color::Indexed(110), Color::Reset), "#87afd7");
+        assert_eq!(css_color(Color::Indexed(231), Color::Reset), "#ffffff");
+        assert_eq!(css_color(Color::Indexed(232), Color::Reset), "#080808");
+        assert_eq!(css_color(Color::Indexed(255), Color::Reset), "#eeeeee");
+    }
+
+    #[test]
+    fn reset_takes_the_default() {
+        assert_eq!(css_color(Color::Reset, Color::Green), "#00cd00");
+        assert_eq!(css_color(Color::Reset, Color::Reset), "#ffffff");
+
+        let theme = Theme::default();
+        assert_eq!(theme.background(Color::Reset), "#263238");
+        assert_eq!(theme.foreground(Color::Reset), "#ffffff");
+        assert_eq!(theme.foreground(Color::Black), "#000000");
+    }
+}
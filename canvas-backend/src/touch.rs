@@ -0,0 +1,317 @@
+//! Touch input on the canvas, translated into the mouse and scroll input of a terminal.
+
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use wasm_bindgen::{prelude::Closure, JsCast};
+use web_sys::{window, HtmlCanvasElement, PointerEvent};
+
+use crate::{
+    dom::{Listener, Timeout},
+    event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind},
+    screen::Screen,
+};
+
+/// How far a finger may move, in CSS pixels, and still tap or long-press.
+const TAP_SLOP: f64 = 10.0;
+
+/// How long a finger has to rest for a long-press, in milliseconds.
+const LONG_PRESS: i32 = 500;
+
+/// The share of its speed a fling keeps every 16 milliseconds.
+const FLING_FRICTION: f64 = 0.95;
+
+/// The slowest a fling scrolls before it stops, in rows per millisecond.
+const FLING_MIN_VELOCITY: f64 = 0.002;
+
+/// What the fingers on the canvas are doing.
+#[derive(Debug, Default)]
+enum Gesture {
+    /// No finger is down.
+    #[default]
+    None,
+    /// A finger is down and has hardly moved, so it may still tap or long-press.
+    Press { start: (f64, f64) },
+    /// A finger is swiping vertically.
+    Swipe {
+        /// The cell the swipe started on.
+        cell: (u16, u16),
+        /// The last position of the finger.
+        y: f64,
+        /// When the finger last moved, in milliseconds since the epoch.
+        time: f64,
+        /// The speed of the finger in rows per millisecond, downwards.
+        velocity: f64,
+    },
+    /// Two fingers are pinching.
+    Pinch {
+        /// The distance between the fingers when they started.
+        distance: f64,
+        /// The font size when they started.
+        size: f64,
+    },
+    /// The gesture is over, e.g. after a long-press, and the remaining fingers are ignored.
+    Done,
+}
+
+/// The state shared by the touch listeners and timers.
+#[derive(Debug, Default)]
+struct Touch {
+    /// The fingers on the canvas, by pointer id, with their positions.
+    points: Vec<(i32, (f64, f64))>,
+    gesture: Gesture,
+    /// Swiped rows not scrolled yet.
+    pending: f64,
+    /// The speed of the fling in rows per millisecond, downwards, zero if not flinging.
+    fling: f64,
+    long_press: Option<Timeout>,
+}
+
+impl Touch {
+    /// Scrolls `rows` rows, positive downwards in the direction the finger moves, for a swipe
+    /// starting on `cell`.
+    fn scroll(&mut self, screen: &mut Screen, cell: (u16, u16), rows: f64) {
+        self.pending += rows;
+        let whole = self.pending.trunc();
+        if whole == 0.0 {
+            return;
+        }
+        self.pending -= whole;
+        let rows = whole as isize;
+        if !screen.mouse_capture {
+            // Moving the content down reveals the rows above it.
+            screen.scroll_view(rows);
+            return;
+        }
+        let kind = if rows > 0 {
+            MouseEventKind::ScrollUp
+        } else {
+            MouseEventKind::ScrollDown
+        };
+        for _ in 0..rows.unsigned_abs() {
+            emit(screen, kind, cell);
+        }
+    }
+
+    /// Returns the distance between the first two fingers.
+    fn spread(&self) -> Option<f64> {
+        match self.points.as_slice() {
+            [(_, (x1, y1)), (_, (x2, y2)), ..] => Some((x2 - x1).hypot(y2 - y1)),
+            _ => None,
+        }
+    }
+}
+
+fn emit(screen: &Screen, kind: MouseEventKind, (column, row): (u16, u16)) {
+    screen.on_event.emit(Event::Mouse(MouseEvent {
+        kind,
+        column,
+        row,
+        modifiers: KeyModifiers::empty(),
+    }));
+}
+
+/// Reports a press and release of `button` on the cell at `position`, while the mouse is
+/// captured.
+fn click(screen: &Screen, button: MouseButton, (x, y): (f64, f64)) {
+    if !screen.mouse_capture {
+        return;
+    }
+    if let Some(cell) = screen.cell_at(x, y) {
+        emit(screen, MouseEventKind::Down(button), cell);
+        emit(screen, MouseEventKind::Up(button), cell);
+    }
+}
+
+/// Keeps scrolling after a swipe, slowing down until it stops or a finger touches again.
+fn fling(screen: Weak<RefCell<Screen>>, touch: Weak<RefCell<Touch>>, cell: (u16, u16), last: f64) {
+    let frame = Closure::once_into_js(move || {
+        let (Some(screen), Some(touch)) = (screen.upgrade(), touch.upgrade()) else {
+            return;
+        };
+        let now = js_sys::Date::now();
+        let elapsed = now - last;
+        {
+            let mut touch = touch.borrow_mut();
+            touch.fling *= FLING_FRICTION.powf(elapsed / 16.0);
+            if touch.fling.abs() < FLING_MIN_VELOCITY {
+                touch.fling = 0.0;
+                return;
+            }
+            let rows = touch.fling * elapsed;
+            touch.scroll(&mut screen.borrow_mut(), cell, rows);
+        }
+        fling(Rc::downgrade(&screen), Rc::downgrade(&touch), cell, now);
+    });
+    let _ = window()
+        .unwrap()
+        .request_animation_frame(frame.unchecked_ref());
+}
+
+/// Translates touches on the canvas of `screen`: a tap clicks, a long-press right-clicks, a
+/// vertical swipe scrolls with inertia and a pinch zooms the font.
+///
+/// Clicks are reported while the mouse is captured. Swipes are reported as wheel scrolling
+/// then, and scroll the scrollback otherwise. The browser's own panning and zooming of the
+/// canvas are turned off.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let canvas = screen.borrow().canvas();
+    let _ = canvas.style().set_property("touch-action", "none");
+    let touch = Rc::new(RefCell::new(Touch::default()));
+
+    let down = {
+        let screen = Rc::clone(screen);
+        let touch = Rc::clone(&touch);
+        Listener::new(&canvas, "pointerdown", move |event: PointerEvent| {
+            if event.pointer_type() != "touch" {
+                return;
+            }
+            let position = (event.client_x().into(), event.client_y().into());
+            let mut state = touch.borrow_mut();
+            state.points.push((event.pointer_id(), position));
+            state.fling = 0.0;
+            state.long_press = None;
+            state.gesture = match (&state.gesture, state.points.len()) {
+                (Gesture::None, 1) => {
+                    let screen = Rc::downgrade(&screen);
+                    let weak = Rc::downgrade(&touch);
+                    state.long_press = Some(Timeout::new(LONG_PRESS, move || {
+                        let (Some(screen), Some(touch)) = (screen.upgrade(), weak.upgrade()) else {
+                            return;
+                        };
+                        let mut touch = touch.borrow_mut();
+                        if let Gesture::Press { start } = touch.gesture {
+                            touch.gesture = Gesture::Done;
+                            click(&screen.borrow(), MouseButton::Right, start);
+                        }
+                    }));
+                    state.pending = 0.0;
+                    Gesture::Press { start: position }
+                }
+                (Gesture::Press { .. } | Gesture::Swipe { .. }, 2) => Gesture::Pinch {
+                    distance: state.spread().unwrap_or_default(),
+                    size: screen.borrow().font.size,
+                },
+                _ => Gesture::Done,
+            };
+        })
+    };
+
+    let moved = {
+        let screen = Rc::clone(screen);
+        let touch = Rc::clone(&touch);
+        Listener::new(&canvas, "pointermove", move |event: PointerEvent| {
+            if event.pointer_type() != "touch" {
+                return;
+            }
+            let position: (f64, f64) = (event.client_x().into(), event.client_y().into());
+            let mut touch = touch.borrow_mut();
+            let Some(point) = touch
+                .points
+                .iter_mut()
+                .find(|(id, _)| *id == event.pointer_id())
+            else {
+                return;
+            };
+            point.1 = position;
+            let now = js_sys::Date::now();
+
+            match touch.gesture {
+                Gesture::Press { start } => {
+                    let (dx, dy) = (position.0 - start.0, position.1 - start.1);
+                    if dx.hypot(dy) < TAP_SLOP {
+                        return;
+                    }
+                    touch.long_press = None;
+                    let mut screen = screen.borrow_mut();
+                    touch.gesture = match screen.nearest_cell(start.0, start.1) {
+                        Some(cell) if dy.abs() > dx.abs() => {
+                            let rows = dy / screen.metrics.height;
+                            touch.scroll(&mut screen, cell, rows);
+                            Gesture::Swipe {
+                                cell,
+                                y: position.1,
+                                time: now,
+                                velocity: 0.0,
+                            }
+                        }
+                        _ => Gesture::Done,
+                    };
+                }
+                Gesture::Swipe {
+                    cell,
+                    y,
+                    time,
+                    velocity,
+                } => {
+                    let mut screen = screen.borrow_mut();
+                    let rows = (position.1 - y) / screen.metrics.height;
+                    let elapsed = (now - time).max(1.0);
+                    touch.gesture = Gesture::Swipe {
+                        cell,
+                        y: position.1,
+                        time: now,
+                        velocity: 0.8 * rows / elapsed + 0.2 * velocity,
+                    };
+                    touch.scroll(&mut screen, cell, rows);
+                }
+                Gesture::Pinch { distance, size } => {
+                    if let Some(spread) = touch.spread().filter(|_| distance > 0.0) {
+                        // Half pixel steps keep the grid from re-measuring on every move.
+                        let zoomed = (size * spread / distance * 2.0).round() / 2.0;
+                        screen.borrow_mut().set_font_size(zoomed);
+                    }
+                }
+                Gesture::None | Gesture::Done => {}
+            }
+        })
+    };
+
+    let up = |kind: &'static str| {
+        let screen = Rc::clone(screen);
+        let touch = Rc::clone(&touch);
+        Listener::new(&canvas, kind, move |event: PointerEvent| {
+            if event.pointer_type() != "touch" {
+                return;
+            }
+            let mut state = touch.borrow_mut();
+            state.points.retain(|(id, _)| *id != event.pointer_id());
+            state.long_press = None;
+            let gesture = std::mem::replace(&mut state.gesture, Gesture::Done);
+            match gesture {
+                Gesture::Press { start } if kind == "pointerup" => {
+                    click(&screen.borrow(), MouseButton::Left, start);
+                }
+                Gesture::Swipe {
+                    cell,
+                    time,
+                    velocity,
+                    ..
+                } => {
+                    // A finger resting before it lifts ends the swipe without a fling.
+                    let now = js_sys::Date::now();
+                    if now - time < 100.0
+                        && velocity.abs() >= FLING_MIN_VELOCITY
+                        && !screen.borrow().blink.reduced_motion
+                    {
+                        state.fling = velocity;
+                        fling(Rc::downgrade(&screen), Rc::downgrade(&touch), cell, now);
+                    }
+                }
+                _ => {}
+            }
+            if state.points.is_empty() {
+                state.gesture = Gesture::None;
+            }
+        })
+    };
+
+    vec![down, moved, up("pointerup"), up("pointercancel")]
+}
+
+/// Gives touches on `canvas` back to the browser.
+pub(crate) fn release(canvas: &HtmlCanvasElement) {
+    let _ = canvas.style().remove_property("touch-action");
+}
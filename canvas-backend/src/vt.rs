@@ -0,0 +1,1063 @@
+//! A VT100/xterm escape sequence interpreter for bytes written to the backend.
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+};
+use unicode_width::UnicodeWidthChar;
+
+use crate::{page, screen::Screen};
+
+/// The longest parameter list kept for a control sequence, counting sub-parameters; further
+/// parameters are dropped.
+const MAX_PARAMS: usize = 32;
+
+/// The longest operating system command kept, in bytes; the rest is dropped.
+const MAX_OSC: usize = 4096;
+
+/// Where the parser is within an escape sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Ground,
+    Escape,
+    /// An escape sequence with intermediate bytes, e.g. a character set designation.
+    EscapeIntermediate,
+    Csi,
+    Osc,
+    /// An `ESC` inside an operating system command, which may start its `ESC \` terminator.
+    OscEscape,
+    /// A device control, privacy message or application program command, which is skipped.
+    Ignore,
+    IgnoreEscape,
+}
+
+/// The colours and attributes printed characters get.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pen {
+    fg: Color,
+    bg: Color,
+    modifier: Modifier,
+}
+
+impl Default for Pen {
+    fn default() -> Self {
+        Pen {
+            fg: Color::Reset,
+            bg: Color::Reset,
+            modifier: Modifier::empty(),
+        }
+    }
+}
+
+/// The cursor state saved by `ESC 7` / `CSI s`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Saved {
+    x: u16,
+    y: u16,
+    pen: Pen,
+}
+
+/// The cells and state the interpreter applies sequences to, the [`Screen`] outside of tests.
+trait Grid {
+    fn buffer(&self) -> &Buffer;
+    fn set_cell(&mut self, x: u16, y: u16, cell: &Cell);
+    /// Resets the cells from `start` up to and including `end`, in reading order.
+    fn clear(&mut self, start: (u16, u16), end: (u16, u16));
+    /// Scrolls the whole screen up by `n` rows into the scrollback.
+    fn scroll_up(&mut self, n: u16);
+    /// Scrolls rows `top` to `bottom` up by `n` rows, or down if negative.
+    fn scroll_rows(&mut self, top: u16, bottom: u16, n: i32);
+    /// Replaces the cells with `buffer` and returns the cells replaced.
+    fn swap_buffer(&mut self, buffer: Buffer) -> Buffer;
+    fn ring_bell(&mut self);
+    fn set_title(&mut self, title: &str);
+    fn set_cursor_visible(&mut self, visible: bool);
+}
+
+impl Grid for Screen {
+    fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    fn set_cell(&mut self, x: u16, y: u16, cell: &Cell) {
+        Screen::set_cell(self, x, y, cell);
+    }
+
+    fn clear(&mut self, start: (u16, u16), end: (u16, u16)) {
+        Screen::clear(self, start, end);
+    }
+
+    fn scroll_up(&mut self, n: u16) {
+        Screen::scroll_up(self, n);
+    }
+
+    fn scroll_rows(&mut self, top: u16, bottom: u16, n: i32) {
+        Screen::scroll_rows(self, top, bottom, n);
+    }
+
+    fn swap_buffer(&mut self, buffer: Buffer) -> Buffer {
+        Screen::swap_buffer(self, buffer)
+    }
+
+    fn ring_bell(&mut self) {
+        Screen::ring_bell(self);
+    }
+
+    fn set_title(&mut self, title: &str) {
+        page::set_title(title);
+    }
+
+    fn set_cursor_visible(&mut self, visible: bool) {
+        self.update_cursor(|cursor| cursor.visible = visible);
+    }
+}
+
+/// Interprets bytes as a VT100/xterm terminal would and applies them to a [`Screen`].
+#[derive(Debug)]
+pub(crate) struct Vt {
+    state: State,
+    /// The bytes of a UTF-8 sequence that is not complete yet.
+    utf8: Vec<u8>,
+    /// The parameters read, each followed by its `:`-separated sub-parameters, e.g.
+    /// `[[38, 2, 0, 255, 0, 0], [1]]` for `38:2::255:0:0;1`.
+    params: Vec<Vec<u16>>,
+    /// The parameter being read, if any of its digits have been.
+    param: Option<u16>,
+    /// Whether the parameter being read is a sub-parameter, i.e. follows a `:`.
+    sub_param: bool,
+    /// The private marker of a control sequence, e.g. `?` in `CSI ? 25 h`.
+    private: Option<u8>,
+    /// The last intermediate byte of a control sequence.
+    intermediate: Option<u8>,
+    /// The bytes of the operating system command being read.
+    osc: Vec<u8>,
+    x: u16,
+    y: u16,
+    /// Whether the cursor sits past the last column, so the next character wraps.
+    pending_wrap: bool,
+    pen: Pen,
+    saved: Option<Saved>,
+    /// The first and last row of the scroll region, `None` for the whole screen.
+    region: Option<(u16, u16)>,
+    autowrap: bool,
+    /// Whether line feeds also return the cursor to the first column, i.e. new line mode.
+    newline: bool,
+    /// The cells and cursor of the main screen while the alternate screen is shown.
+    main: Option<(Buffer, Saved)>,
+}
+
+impl Default for Vt {
+    fn default() -> Self {
+        Vt {
+            state: State::Ground,
+            utf8: Vec::new(),
+            params: Vec::new(),
+            param: None,
+            sub_param: false,
+            private: None,
+            intermediate: None,
+            osc: Vec::new(),
+            x: 0,
+            y: 0,
+            pending_wrap: false,
+            pen: Pen::default(),
+            saved: None,
+            region: None,
+            autowrap: true,
+            // Unlike in a terminal, no line discipline turns `\n` into `\r\n` in front of it.
+            newline: true,
+            main: None,
+        }
+    }
+}
+
+impl Vt {
+    /// Interprets `bytes` and applies them to `screen`.
+    ///
+    /// Sequences may be split across calls; the parser picks up where the last call left off.
+    pub(crate) fn feed(&mut self, screen: &mut Screen, bytes: &[u8]) {
+        let area = screen.buffer.area;
+        if (self.x, self.y) != (screen.cursor.x, screen.cursor.y) {
+            // The cursor was moved through the backend, e.g. by a ratatui draw.
+            self.pending_wrap = false;
+        }
+        self.x = screen.cursor.x.min(area.width.saturating_sub(1));
+        self.y = screen.cursor.y.min(area.height.saturating_sub(1));
+
+        screen.batch(|screen| {
+            self.apply(screen, bytes);
+            screen.detect_links();
+        });
+
+        let (x, y) = (self.x, self.y);
+        screen.badge.activity();
+        screen.update_cursor(|cursor| {
+            cursor.x = x;
+            cursor.y = y;
+        });
+        screen.paint_cursor();
+    }
+
+    /// Interprets `bytes` and applies them to `grid`, starting at the cursor position kept.
+    fn apply(&mut self, grid: &mut impl Grid, bytes: &[u8]) {
+        for &byte in bytes {
+            self.byte(grid, byte);
+        }
+    }
+
+    fn byte(&mut self, screen: &mut impl Grid, byte: u8) {
+        match self.state {
+            State::Ground => match byte {
+                0x1b => {
+                    self.utf8.clear();
+                    self.state = State::Escape;
+                }
+                0x00..=0x1f | 0x7f => {
+                    self.utf8.clear();
+                    self.control(screen, byte);
+                }
+                _ => self.utf8_byte(screen, byte),
+            },
+            State::Escape => match byte {
+                b'[' => {
+                    self.params.clear();
+                    self.param = None;
+                    self.sub_param = false;
+                    self.private = None;
+                    self.intermediate = None;
+                    self.state = State::Csi;
+                }
+                b']' => {
+                    self.osc.clear();
+                    self.state = State::Osc;
+                }
+                b'P' | b'X' | b'^' | b'_' => self.state = State::Ignore,
+                0x20..=0x2f => self.state = State::EscapeIntermediate,
+                0x1b => {}
+                0x00..=0x1f => self.control(screen, byte),
+                _ => {
+                    self.state = State::Ground;
+                    self.escape(screen, byte);
+                }
+            },
+            State::EscapeIntermediate => match byte {
+                // Character set designations and the like are accepted but have no effect.
+                0x30..=0x7e => self.state = State::Ground,
+                0x1b => self.state = State::Escape,
+                0x00..=0x1f => self.control(screen, byte),
+                _ => {}
+            },
+            State::Csi => match byte {
+                b'0'..=b'9' => {
+                    let digit = u16::from(byte - b'0');
+                    self.param = Some(
+                        self.param
+                            .unwrap_or(0)
+                            .saturating_mul(10)
+                            .saturating_add(digit),
+                    );
+                }
+                b';' => {
+                    self.push_param();
+                    self.sub_param = false;
+                }
+                b':' => {
+                    self.push_param();
+                    self.sub_param = true;
+                }
+                b'<'..=b'?' if self.params.is_empty() && self.param.is_none() => {
+                    self.private = Some(byte);
+                }
+                0x20..=0x2f => self.intermediate = Some(byte),
+                0x40..=0x7e => {
+                    self.push_param();
+                    self.state = State::Ground;
+                    self.csi(screen, byte);
+                }
+                0x1b => self.state = State::Escape,
+                0x00..=0x1f => self.control(screen, byte),
+                _ => {}
+            },
+            State::Osc => match byte {
+                0x07 => {
+                    self.state = State::Ground;
+                    self.osc(screen);
+                }
+                0x1b => self.state = State::OscEscape,
+                _ if self.osc.len() < MAX_OSC => self.osc.push(byte),
+                _ => {}
+            },
+            State::OscEscape => {
+                self.state = State::Ground;
+                self.osc(screen);
+                if byte != b'\\' {
+                    self.state = State::Escape;
+                    self.byte(screen, byte);
+                }
+            }
+            State::Ignore => match byte {
+                0x07 => self.state = State::Ground,
+                0x1b => self.state = State::IgnoreEscape,
+                _ => {}
+            },
+            State::IgnoreEscape => {
+                self.state = if byte == b'\\' {
+                    State::Ground
+                } else {
+                    State::Ignore
+                };
+            }
+        }
+    }
+
+    /// Applies the operating system command read, of which only setting the title is
+    /// supported.
+    fn osc(&mut self, screen: &mut impl Grid) {
+        let osc = String::from_utf8_lossy(&self.osc);
+        if let Some(("0" | "2", title)) = osc.split_once(';') {
+            screen.set_title(title);
+        }
+    }
+
+    /// Collects `byte` of a UTF-8 sequence and prints the character once it is complete.
+    fn utf8_byte(&mut self, screen: &mut impl Grid, byte: u8) {
+        self.utf8.push(byte);
+        match std::str::from_utf8(&self.utf8) {
+            Ok(s) => {
+                let c = s.chars().next().unwrap();
+                self.utf8.clear();
+                self.print(screen, c);
+            }
+            Err(err) if err.error_len().is_some() => {
+                self.utf8.clear();
+                self.print(screen, char::REPLACEMENT_CHARACTER);
+            }
+            Err(_) => {}
+        }
+    }
+
+    fn push_param(&mut self) {
+        let param = self.param.take().unwrap_or(0);
+        if self.params.iter().map(Vec::len).sum::<usize>() >= MAX_PARAMS {
+            return;
+        }
+        match self.params.last_mut() {
+            Some(params) if self.sub_param => params.push(param),
+            _ => self.params.push(vec![param]),
+        }
+    }
+
+    /// Returns parameter `i`, or `default` if it is missing or zero.
+    fn param(&self, i: usize, default: u16) -> u16 {
+        match self.params.get(i).map(|params| params[0]) {
+            Some(0) | None => default,
+            Some(param) => param,
+        }
+    }
+
+    /// Returns the parameters without their sub-parameters.
+    fn main_params(&self) -> impl Iterator<Item = u16> + '_ {
+        self.params.iter().map(|params| params[0])
+    }
+
+    /// Returns the first and last row of the scroll region on `screen`.
+    fn region(&self, screen: &impl Grid) -> (u16, u16) {
+        let last = screen.buffer().area.height.saturating_sub(1);
+        match self.region {
+            Some((top, bottom)) if bottom <= last => (top, bottom),
+            _ => (0, last),
+        }
+    }
+
+    fn print(&mut self, screen: &mut impl Grid, c: char) {
+        let area = screen.buffer().area;
+        if area.area() == 0 {
+            return;
+        }
+        let Some(width) = c.width() else {
+            return;
+        };
+        if width == 0 {
+            // A combining character joins the character before it.
+            let x = if self.pending_wrap {
+                self.x
+            } else {
+                self.x.saturating_sub(1)
+            };
+            let mut cell = screen.buffer().get(x, self.y).clone();
+            cell.symbol.push(c);
+            screen.set_cell(x, self.y, &cell);
+            return;
+        }
+
+        let width = width as u16;
+        if self.pending_wrap || self.x + width > area.width {
+            if self.autowrap {
+                self.x = 0;
+                self.line_feed(screen);
+            } else {
+                self.x = area.width.saturating_sub(width);
+            }
+        }
+        self.pending_wrap = false;
+
+        let mut cell = Cell {
+            fg: self.pen.fg,
+            bg: self.pen.bg,
+            modifier: self.pen.modifier,
+            ..Cell::default()
+        };
+        cell.set_char(c);
+        screen.set_cell(self.x, self.y, &cell);
+        for x in self.x + 1..(self.x + width).min(area.width) {
+            // The cells a wide character covers, blank like ratatui leaves them.
+            let covered = Cell {
+                bg: self.pen.bg,
+                ..Cell::default()
+            };
+            screen.set_cell(x, self.y, &covered);
+        }
+
+        if self.x + width >= area.width {
+            self.x = area.width - 1;
+            self.pending_wrap = self.autowrap;
+        } else {
+            self.x += width;
+        }
+    }
+
+    fn control(&mut self, screen: &mut impl Grid, byte: u8) {
+        let area = screen.buffer().area;
+        match byte {
+            0x07 => screen.ring_bell(),
+            0x08 => {
+                self.x = self.x.saturating_sub(1);
+                self.pending_wrap = false;
+            }
+            0x09 => {
+                self.x = ((self.x / 8 + 1) * 8).min(area.width.saturating_sub(1));
+                self.pending_wrap = false;
+            }
+            0x0a..=0x0c => {
+                if self.newline {
+                    self.x = 0;
+                }
+                self.line_feed(screen);
+            }
+            0x0d => {
+                self.x = 0;
+                self.pending_wrap = false;
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the cursor down a row, scrolling the scroll region at its bottom.
+    fn line_feed(&mut self, screen: &mut impl Grid) {
+        self.pending_wrap = false;
+        let (top, bottom) = self.region(screen);
+        if self.y == bottom {
+            self.scroll(screen, top, bottom, 1);
+        } else if self.y + 1 < screen.buffer().area.height {
+            self.y += 1;
+        }
+    }
+
+    /// Moves the cursor up a row, scrolling the scroll region down at its top.
+    fn reverse_line_feed(&mut self, screen: &mut impl Grid) {
+        self.pending_wrap = false;
+        let (top, bottom) = self.region(screen);
+        if self.y == top {
+            self.scroll(screen, top, bottom, -1);
+        } else {
+            self.y = self.y.saturating_sub(1);
+        }
+    }
+
+    /// Scrolls rows `top` to `bottom` up by `n` rows, or down if negative.
+    ///
+    /// Rows scrolled off the whole main screen go into the scrollback like with
+    /// [`append_lines`](ratatui::backend::Backend::append_lines).
+    fn scroll(&mut self, screen: &mut impl Grid, top: u16, bottom: u16, n: i32) {
+        let last = screen.buffer().area.height.saturating_sub(1);
+        if n > 0 && top == 0 && bottom == last && self.main.is_none() {
+            screen.scroll_up(n.min(i32::from(u16::MAX)) as u16);
+        } else {
+            screen.scroll_rows(top, bottom, n);
+        }
+    }
+
+    fn escape(&mut self, screen: &mut impl Grid, byte: u8) {
+        match byte {
+            b'7' => self.save_cursor(),
+            b'8' => self.restore_cursor(screen),
+            b'D' => self.line_feed(screen),
+            b'E' => {
+                self.x = 0;
+                self.line_feed(screen);
+            }
+            b'M' => self.reverse_line_feed(screen),
+            b'c' => self.reset(screen),
+            _ => {}
+        }
+    }
+
+    fn save_cursor(&mut self) {
+        self.saved = Some(Saved {
+            x: self.x,
+            y: self.y,
+            pen: self.pen,
+        });
+    }
+
+    fn restore_cursor(&mut self, screen: &impl Grid) {
+        let saved = self.saved.unwrap_or(Saved {
+            x: 0,
+            y: 0,
+            pen: Pen::default(),
+        });
+        self.move_to(screen, saved.x, saved.y);
+        self.pen = saved.pen;
+    }
+
+    /// Moves the cursor to (`x`, `y`), kept on the screen.
+    fn move_to(&mut self, screen: &impl Grid, x: u16, y: u16) {
+        let area = screen.buffer().area;
+        self.x = x.min(area.width.saturating_sub(1));
+        self.y = y.min(area.height.saturating_sub(1));
+        self.pending_wrap = false;
+    }
+
+    /// Returns to the initial state and clears the screen.
+    fn reset(&mut self, screen: &mut impl Grid) {
+        self.set_alternate(screen, false);
+        *self = Vt::default();
+        let area = screen.buffer().area;
+        screen.clear(
+            (0, 0),
+            (area.width.saturating_sub(1), area.height.saturating_sub(1)),
+        );
+        screen.set_cursor_visible(true);
+    }
+
+    fn csi(&mut self, screen: &mut impl Grid, byte: u8) {
+        if self.intermediate.is_some() {
+            return;
+        }
+        if self.private.is_some() {
+            match (self.private, byte) {
+                (Some(b'?'), b'h') => self.private_modes(screen, true),
+                (Some(b'?'), b'l') => self.private_modes(screen, false),
+                _ => {}
+            }
+            return;
+        }
+
+        let area = screen.buffer().area;
+        let (right, last) = (area.width.saturating_sub(1), area.height.saturating_sub(1));
+        let (top, bottom) = self.region(screen);
+        let n = self.param(0, 1);
+        // Vertical movement stops at the scroll region when starting inside it.
+        let (up_limit, down_limit) = if (top..=bottom).contains(&self.y) {
+            (top, bottom)
+        } else {
+            (0, last)
+        };
+        let (x, y) = (self.x, self.y);
+
+        match byte {
+            b'A' => self.move_to(screen, x, y.saturating_sub(n).max(up_limit.min(y))),
+            b'B' | b'e' => self.move_to(screen, x, y.saturating_add(n).min(down_limit.max(y))),
+            b'C' | b'a' => self.move_to(screen, x.saturating_add(n), y),
+            b'D' => self.move_to(screen, x.saturating_sub(n), y),
+            b'E' => self.move_to(screen, 0, y.saturating_add(n).min(down_limit.max(y))),
+            b'F' => self.move_to(screen, 0, y.saturating_sub(n).max(up_limit.min(y))),
+            b'G' | b'`' => self.move_to(screen, n - 1, y),
+            b'd' => self.move_to(screen, x, n - 1),
+            b'H' | b'f' => self.move_to(screen, self.param(1, 1) - 1, n - 1),
+            b'J' => match self.param(0, 0) {
+                0 => screen.clear((x, y), (right, last)),
+                1 => screen.clear((0, 0), (x, y)),
+                _ => screen.clear((0, 0), (right, last)),
+            },
+            b'K' => match self.param(0, 0) {
+                0 => screen.clear((x, y), (right, y)),
+                1 => screen.clear((0, y), (x, y)),
+                _ => screen.clear((0, y), (right, y)),
+            },
+            b'S' => self.scroll(screen, top, bottom, i32::from(n)),
+            b'T' => self.scroll(screen, top, bottom, -i32::from(n)),
+            b'L' | b'M' if (top..=bottom).contains(&y) => {
+                let n = if byte == b'L' {
+                    -i32::from(n)
+                } else {
+                    i32::from(n)
+                };
+                screen.scroll_rows(y, bottom, n);
+                self.move_to(screen, 0, y);
+            }
+            b'@' | b'P' | b'X' => self.edit_line(screen, byte, n),
+            b'm' => self.sgr(),
+            b'r' => {
+                let (new_top, new_bottom) = (self.param(0, 1) - 1, self.param(1, last + 1) - 1);
+                if new_top < new_bottom && new_bottom <= last {
+                    self.region = Some((new_top, new_bottom)).filter(|&r| r != (0, last));
+                    self.move_to(screen, 0, 0);
+                }
+            }
+            b's' => self.save_cursor(),
+            b'u' => self.restore_cursor(screen),
+            b'h' | b'l' if self.main_params().any(|mode| mode == 20) => {
+                self.newline = byte == b'h';
+            }
+            _ => {}
+        }
+    }
+
+    /// Inserts (`@`), deletes (`P`) or erases (`X`) `n` characters at the cursor.
+    fn edit_line(&mut self, screen: &mut impl Grid, byte: u8, n: u16) {
+        let width = screen.buffer().area.width;
+        let y = self.y;
+        let row: Vec<Cell> = (0..width)
+            .map(|x| screen.buffer().get(x, y).clone())
+            .collect();
+        let blank = Cell {
+            bg: self.pen.bg,
+            ..Cell::default()
+        };
+
+        for x in self.x..width {
+            let i = usize::from(x);
+            let cell = match byte {
+                b'@' => usize::from(x)
+                    .checked_sub(usize::from(n))
+                    .filter(|&from| from >= usize::from(self.x))
+                    .map_or(&blank, |from| &row[from]),
+                b'P' => row.get(i + usize::from(n)).unwrap_or(&blank),
+                _ if x < self.x.saturating_add(n) => &blank,
+                _ => &row[i],
+            };
+            if *cell != row[i] {
+                screen.set_cell(x, y, cell);
+            }
+        }
+        self.pending_wrap = false;
+    }
+
+    /// Applies `CSI ? … h` (`set`) or `CSI ? … l` private modes.
+    fn private_modes(&mut self, screen: &mut impl Grid, set: bool) {
+        let modes: Vec<u16> = self.main_params().collect();
+        for mode in modes {
+            match mode {
+                7 => self.autowrap = set,
+                25 => screen.set_cursor_visible(set),
+                47 | 1047 => self.set_alternate(screen, set),
+                1049 => {
+                    if set {
+                        self.save_cursor();
+                        self.set_alternate(screen, true);
+                    } else {
+                        self.set_alternate(screen, false);
+                        self.restore_cursor(screen);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Switches to a blank alternate screen, or back to the main screen as it was left.
+    fn set_alternate(&mut self, screen: &mut impl Grid, alternate: bool) {
+        let cursor = Saved {
+            x: self.x,
+            y: self.y,
+            pen: self.pen,
+        };
+        match (alternate, self.main.take()) {
+            (true, None) => {
+                let main = screen.swap_buffer(Buffer::empty(screen.buffer().area));
+                self.main = Some((main, cursor));
+            }
+            (false, Some((main, saved))) => {
+                screen.swap_buffer(main);
+                self.move_to(screen, saved.x, saved.y);
+            }
+            (_, main) => self.main = main,
+        }
+    }
+
+    /// Applies Select Graphic Rendition parameters to the pen.
+    fn sgr(&mut self) {
+        if self.params.is_empty() {
+            self.pen = Pen::default();
+            return;
+        }
+        let mut params = self.params.iter();
+        while let Some(group) = params.next() {
+            let (param, sub_params) = (group[0], &group[1..]);
+            let modifier = &mut self.pen.modifier;
+            match param {
+                0 => self.pen = Pen::default(),
+                1 => modifier.insert(Modifier::BOLD),
+                2 => modifier.insert(Modifier::DIM),
+                3 => modifier.insert(Modifier::ITALIC),
+                // `4:0` turns underlining off, while `4:3` and the like pick a style.
+                4 if sub_params.first() == Some(&0) => modifier.remove(Modifier::UNDERLINED),
+                4 | 21 => modifier.insert(Modifier::UNDERLINED),
+                5 => modifier.insert(Modifier::SLOW_BLINK),
+                6 => modifier.insert(Modifier::RAPID_BLINK),
+                7 => modifier.insert(Modifier::REVERSED),
+                8 => modifier.insert(Modifier::HIDDEN),
+                9 => modifier.insert(Modifier::CROSSED_OUT),
+                22 => modifier.remove(Modifier::BOLD | Modifier::DIM),
+                23 => modifier.remove(Modifier::ITALIC),
+                24 => modifier.remove(Modifier::UNDERLINED),
+                25 => modifier.remove(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+                27 => modifier.remove(Modifier::REVERSED),
+                28 => modifier.remove(Modifier::HIDDEN),
+                29 => modifier.remove(Modifier::CROSSED_OUT),
+                30..=37 => self.pen.fg = ansi_color(param - 30),
+                38 => self.pen.fg = extended_color(sub_params, &mut params).unwrap_or(self.pen.fg),
+                39 => self.pen.fg = Color::Reset,
+                40..=47 => self.pen.bg = ansi_color(param - 40),
+                48 => self.pen.bg = extended_color(sub_params, &mut params).unwrap_or(self.pen.bg),
+                49 => self.pen.bg = Color::Reset,
+                90..=97 => self.pen.fg = ansi_color(param - 90 + 8),
+                100..=107 => self.pen.bg = ansi_color(param - 100 + 8),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Returns the named colour of ANSI colour `index`, 0 to 15.
+fn ansi_color(index: u16) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::Gray,
+        8 => Color::DarkGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+/// Reads the colour of an SGR 38 or 48 from its `sub_params`, e.g. `5:n`, `2:r:g:b` or
+/// `2::r:g:b` with the colour space left out, or else from the `5;n` or `2;r;g;b` parameters
+/// following it.
+fn extended_color<'a>(
+    sub_params: &[u16],
+    params: &mut impl Iterator<Item = &'a Vec<u16>>,
+) -> Option<Color> {
+    let component = |v: u16| v.min(255) as u8;
+    match *sub_params {
+        [] => {
+            let mut next = || params.next().map(|params| component(params[0]));
+            match next()? {
+                5 => next().map(Color::Indexed),
+                2 => Some(Color::Rgb(next()?, next()?, next()?)),
+                _ => None,
+            }
+        }
+        [5, n, ..] => Some(Color::Indexed(component(n))),
+        [2, _, r, g, b, ..] | [2, r, g, b] => {
+            Some(Color::Rgb(component(r), component(g), component(b)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::prelude::Rect;
+
+    use super::*;
+
+    /// A grid of cells without a canvas, keeping what the screen would show.
+    struct Term {
+        buffer: Buffer,
+        scrollback: Vec<String>,
+        title: String,
+        cursor_visible: bool,
+    }
+
+    impl Grid for Term {
+        fn buffer(&self) -> &Buffer {
+            &self.buffer
+        }
+
+        fn set_cell(&mut self, x: u16, y: u16, cell: &Cell) {
+            *self.buffer.get_mut(x, y) = cell.clone();
+        }
+
+        fn clear(&mut self, start: (u16, u16), end: (u16, u16)) {
+            let start = self.buffer.index_of(start.0, start.1);
+            let end = self.buffer.index_of(end.0, end.1);
+            self.buffer.content[start..=end]
+                .iter_mut()
+                .for_each(Cell::reset);
+        }
+
+        fn scroll_up(&mut self, n: u16) {
+            for _ in 0..n {
+                self.scrollback.push(row(&self.buffer, 0));
+                self.scroll_rows(0, self.buffer.area.height - 1, 1);
+            }
+        }
+
+        fn scroll_rows(&mut self, top: u16, bottom: u16, n: i32) {
+            let width = usize::from(self.buffer.area.width);
+            let rows = &mut self.buffer.content
+                [usize::from(top) * width..(usize::from(bottom) + 1) * width];
+            let shift = (n.unsigned_abs() as usize * width).min(rows.len());
+            if n > 0 {
+                rows.rotate_left(shift);
+                let len = rows.len();
+                rows[len - shift..].iter_mut().for_each(Cell::reset);
+            } else {
+                rows.rotate_right(shift);
+                rows[..shift].iter_mut().for_each(Cell::reset);
+            }
+        }
+
+        fn swap_buffer(&mut self, buffer: Buffer) -> Buffer {
+            std::mem::replace(&mut self.buffer, buffer)
+        }
+
+        fn ring_bell(&mut self) {}
+
+        fn set_title(&mut self, title: &str) {
+            self.title = title.to_string();
+        }
+
+        fn set_cursor_visible(&mut self, visible: bool) {
+            self.cursor_visible = visible;
+        }
+    }
+
+    /// Returns the text of row `y`, without trailing blanks.
+    fn row(buffer: &Buffer, y: u16) -> String {
+        let text: String = (0..buffer.area.width)
+            .map(|x| buffer.get(x, y).symbol.as_str())
+            .collect();
+        text.trim_end().to_string()
+    }
+
+    fn rows(term: &Term) -> Vec<String> {
+        (0..term.buffer.area.height)
+            .map(|y| row(&term.buffer, y))
+            .collect()
+    }
+
+    /// Feeds `input` to a new interpreter on a blank `width` x `height` grid.
+    fn run(width: u16, height: u16, input: &str) -> (Vt, Term) {
+        let mut term = Term {
+            buffer: Buffer::empty(Rect::new(0, 0, width, height)),
+            scrollback: Vec::new(),
+            title: String::new(),
+            cursor_visible: true,
+        };
+        let mut vt = Vt::default();
+        vt.apply(&mut term, input.as_bytes());
+        (vt, term)
+    }
+
+    #[test]
+    fn line_feeds_return_to_the_first_column() {
+        let (_, term) = run(10, 3, "one\ntwo\r\nthree");
+        assert_eq!(rows(&term), ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn line_feeds_only_move_down_without_new_line_mode() {
+        let (_, term) = run(10, 2, "\x1b[20lone\ntwo");
+        assert_eq!(rows(&term), ["one", "   two"]);
+    }
+
+    #[test]
+    fn wraps_at_the_last_column() {
+        let (_, term) = run(5, 2, "abcdefg");
+        assert_eq!(rows(&term), ["abcde", "fg"]);
+
+        let (_, term) = run(5, 2, "\x1b[?7labcdefg");
+        assert_eq!(rows(&term), ["abcdg", ""]);
+    }
+
+    #[test]
+    fn scrolls_into_the_scrollback() {
+        let (vt, term) = run(5, 2, "a\nb\nc");
+        assert_eq!(rows(&term), ["b", "c"]);
+        assert_eq!(term.scrollback, ["a"]);
+        assert_eq!((vt.x, vt.y), (1, 1));
+    }
+
+    #[test]
+    fn moves_the_cursor() {
+        let (_, term) = run(10, 4, "\x1b[3;4HX\x1b[AY\x1b[2DZ\x1b[BW");
+        assert_eq!(rows(&term), ["", "   ZY", "   XW", ""]);
+
+        let (vt, _) = run(10, 4, "\x1b[99;99H");
+        assert_eq!((vt.x, vt.y), (9, 3));
+        let (vt, _) = run(10, 4, "\x1b[2;2H\x1b[H");
+        assert_eq!((vt.x, vt.y), (0, 0));
+        let (vt, _) = run(10, 4, "\x1b[3G\x1b[2d\x1b[C");
+        assert_eq!((vt.x, vt.y), (3, 1));
+    }
+
+    #[test]
+    fn saves_and_restores_the_cursor() {
+        let (vt, _) = run(10, 4, "\x1b[2;3H\x1b7\x1b[H\x1b8");
+        assert_eq!((vt.x, vt.y), (2, 1));
+    }
+
+    #[test]
+    fn erases_in_line_and_display() {
+        let lines = "abcdef\nabcdef\nabcdef";
+        let (_, term) = run(10, 3, &format!("{lines}\x1b[2;3H\x1b[K"));
+        assert_eq!(rows(&term), ["abcdef", "ab", "abcdef"]);
+
+        let (_, term) = run(10, 3, &format!("{lines}\x1b[1;3H\x1b[1K"));
+        assert_eq!(rows(&term), ["   def", "abcdef", "abcdef"]);
+
+        let (_, term) = run(10, 3, &format!("{lines}\x1b[2;4H\x1b[J"));
+        assert_eq!(rows(&term), ["abcdef", "abc", ""]);
+
+        let (_, term) = run(10, 3, &format!("{lines}\x1b[2;4H\x1b[1J"));
+        assert_eq!(rows(&term), ["", "    ef", "abcdef"]);
+
+        let (_, term) = run(10, 3, &format!("{lines}\x1b[2J"));
+        assert_eq!(rows(&term), ["", "", ""]);
+    }
+
+    #[test]
+    fn inserts_deletes_and_erases_characters() {
+        let (_, term) = run(10, 1, "abcdef\r\x1b[2C\x1b[2@");
+        assert_eq!(rows(&term), ["ab  cdef"]);
+
+        let (_, term) = run(10, 1, "abcdef\r\x1b[C\x1b[2P");
+        assert_eq!(rows(&term), ["adef"]);
+
+        let (_, term) = run(10, 1, "abcdef\r\x1b[C\x1b[2X");
+        assert_eq!(rows(&term), ["a  def"]);
+
+        let (_, term) = run(6, 1, "abcdef\r\x1b[2@");
+        assert_eq!(rows(&term), ["  abcd"]);
+    }
+
+    #[test]
+    fn scrolls_inside_the_scroll_region() {
+        let lines = "1\n2\n3\n4\n5";
+        let (_, term) = run(5, 5, &format!("{lines}\x1b[2;4r\x1b[4;1H\nX"));
+        assert_eq!(rows(&term), ["1", "3", "4", "X", "5"]);
+        assert!(term.scrollback.is_empty());
+
+        let (_, term) = run(5, 5, &format!("{lines}\x1b[2;4r\x1b[2;1H\x1bM"));
+        assert_eq!(rows(&term), ["1", "", "2", "3", "5"]);
+
+        let (_, term) = run(5, 5, &format!("{lines}\x1b[2;4r\x1b[3;1H\x1b[L"));
+        assert_eq!(rows(&term), ["1", "2", "", "3", "5"]);
+
+        let (_, term) = run(5, 5, &format!("{lines}\x1b[2;4r\x1b[2;1H\x1b[2M"));
+        assert_eq!(rows(&term), ["1", "4", "", "", "5"]);
+
+        let (_, term) = run(5, 5, &format!("{lines}\x1b[2;4r\x1b[S"));
+        assert_eq!(rows(&term), ["1", "3", "4", "", "5"]);
+    }
+
+    #[test]
+    fn cursor_movement_stops_at_the_scroll_region() {
+        let (vt, _) = run(5, 5, "\x1b[2;4r\x1b[3;1H\x1b[9A");
+        assert_eq!(vt.y, 1);
+        let (vt, _) = run(5, 5, "\x1b[2;4r\x1b[3;1H\x1b[9B");
+        assert_eq!(vt.y, 3);
+        let (vt, _) = run(5, 5, "\x1b[2;4r\x1b[5;1H\x1b[9A");
+        assert_eq!(vt.y, 0);
+    }
+
+    #[test]
+    fn applies_sgr_attributes() {
+        let (_, term) = run(5, 1, "\x1b[1;3;31;42mX\x1b[22;39mY\x1b[0mZ");
+        let x = term.buffer.get(0, 0);
+        assert_eq!((x.fg, x.bg), (Color::Red, Color::Green));
+        assert_eq!(x.modifier, Modifier::BOLD | Modifier::ITALIC);
+        let y = term.buffer.get(1, 0);
+        assert_eq!(
+            (y.fg, y.bg, y.modifier),
+            (Color::Reset, Color::Green, Modifier::ITALIC)
+        );
+        assert_eq!(
+            term.buffer.get(2, 0),
+            &Cell::default().set_symbol("Z").clone()
+        );
+    }
+
+    #[test]
+    fn applies_extended_colors() {
+        let (_, term) = run(5, 1, "\x1b[38;5;208;48;2;1;2;300mX\x1b[94;101mY");
+        let x = term.buffer.get(0, 0);
+        assert_eq!((x.fg, x.bg), (Color::Indexed(208), Color::Rgb(1, 2, 255)));
+        let y = term.buffer.get(1, 0);
+        assert_eq!((y.fg, y.bg), (Color::LightBlue, Color::LightRed));
+    }
+
+    #[test]
+    fn applies_colon_separated_colors() {
+        let (_, term) = run(
+            5,
+            1,
+            "\x1b[38:2::10:20:30mA\x1b[38:2:40:50:60mB\x1b[48:5:17;1mC",
+        );
+        assert_eq!(term.buffer.get(0, 0).fg, Color::Rgb(10, 20, 30));
+        assert_eq!(term.buffer.get(1, 0).fg, Color::Rgb(40, 50, 60));
+        let c = term.buffer.get(2, 0);
+        assert_eq!((c.fg, c.bg), (Color::Rgb(40, 50, 60), Color::Indexed(17)));
+        assert_eq!(c.modifier, Modifier::BOLD);
+    }
+
+    #[test]
+    fn reads_sub_parameters_apart_from_parameters() {
+        // An underline colour is not supported, and its components are not attributes.
+        let (_, term) = run(5, 1, "\x1b[58:2::1:2:3mA\x1b[4:3mB\x1b[4:0mC");
+        assert_eq!(term.buffer.get(0, 0).modifier, Modifier::empty());
+        assert_eq!(term.buffer.get(1, 0).modifier, Modifier::UNDERLINED);
+        assert_eq!(term.buffer.get(2, 0).modifier, Modifier::empty());
+    }
+
+    #[test]
+    fn continues_sequences_split_across_writes() {
+        let (mut vt, mut term) = run(5, 1, "\x1b[3");
+        // The second half of the escape sequence and the first byte of `é`.
+        vt.apply(&mut term, b"1mX\xc3");
+        vt.apply(&mut term, b"\xa9");
+        assert_eq!(term.buffer.get(0, 0).fg, Color::Red);
+        assert_eq!(rows(&term), ["X\u{e9}"]);
+    }
+
+    #[test]
+    fn switches_to_the_alternate_screen_and_back() {
+        let (vt, term) = run(10, 2, "main\x1b[?1049h\x1b[Halt");
+        assert_eq!(rows(&term), ["alt", ""]);
+        assert!(vt.main.is_some());
+
+        let (vt, term) = run(10, 2, "main\x1b[?1049halt\x1b[?1049l");
+        assert_eq!(rows(&term), ["main", ""]);
+        assert_eq!((vt.x, vt.y), (4, 0));
+    }
+
+    #[test]
+    fn sets_the_title_and_cursor_visibility() {
+        let (_, term) = run(5, 1, "\x1b]2;hello\x1b\\\x1b[?25l");
+        assert_eq!(term.title, "hello");
+        assert!(!term.cursor_visible);
+    }
+}
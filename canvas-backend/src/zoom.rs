@@ -0,0 +1,60 @@
+//! Built-in font zoom with Ctrl+= / Ctrl+- / Ctrl+0 and Ctrl+wheel.
+
+use std::{cell::RefCell, rc::Rc};
+
+use web_sys::{window, KeyboardEvent, WheelEvent};
+
+use crate::{dom::Listener, screen::Screen};
+
+/// The smallest font size zooming goes down to, in CSS pixels.
+pub const MIN_FONT_SIZE: f64 = 6.0;
+/// The largest font size zooming goes up to, in CSS pixels.
+pub const MAX_FONT_SIZE: f64 = 72.0;
+
+const STEP: f64 = 1.0;
+
+/// Listens for zoom keys while the canvas has focus and Ctrl+wheel on the canvas of `screen`.
+///
+/// Ctrl+0 restores the font size the screen had when this was called.
+pub(crate) fn listen(screen: &Rc<RefCell<Screen>>) -> Vec<Listener> {
+    let default = screen.borrow().font.size;
+    let document = window().unwrap().document().unwrap();
+    let canvas = screen.borrow().canvas();
+
+    let keys = {
+        let screen = Rc::clone(screen);
+        Listener::active(&document, "keydown", move |event: KeyboardEvent| {
+            if !(event.ctrl_key() || event.meta_key()) || !screen.borrow().has_focus() {
+                return;
+            }
+            let size = screen.borrow().font.size;
+            let size = match event.key().as_str() {
+                "=" | "+" => size + STEP,
+                "-" | "_" => size - STEP,
+                "0" => default,
+                _ => return,
+            };
+            event.prevent_default();
+            screen.borrow_mut().set_font_size(size);
+        })
+    };
+
+    let wheel = {
+        let screen = Rc::clone(screen);
+        Listener::active(&canvas, "wheel", move |event: WheelEvent| {
+            if !event.ctrl_key() || event.delta_y() == 0.0 {
+                return;
+            }
+            event.prevent_default();
+            let size = screen.borrow().font.size;
+            let size = if event.delta_y() < 0.0 {
+                size + STEP
+            } else {
+                size - STEP
+            };
+            screen.borrow_mut().set_font_size(size);
+        })
+    };
+
+    vec![keys, wheel]
+}
@@ -0,0 +1,70 @@
+//! Copying to the clipboard.
+//!
+//! Run in a browser with `wasm-pack test --headless --chrome canvas-backend`.
+
+#![cfg(target_arch = "wasm32")]
+
+use std::{cell::RefCell, io, rc::Rc};
+
+use canvas_backend::{event::Event, CanvasBackend};
+use js_sys::{Object, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+use web_sys::{window, HtmlCanvasElement};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Resolves after the current task and the ones already queued.
+async fn next_task() {
+    let promise = Promise::new(&mut |resolve, _| {
+        window()
+            .unwrap()
+            .set_timeout_with_callback(&resolve)
+            .unwrap();
+    });
+    JsFuture::from(promise).await.unwrap();
+}
+
+#[wasm_bindgen_test]
+async fn copying_through_a_text_area_reports_no_focus_change() {
+    // Leave only the fallback through a hidden text area.
+    let descriptor = Object::new();
+    Reflect::set(&descriptor, &"value".into(), &JsValue::UNDEFINED).unwrap();
+    Reflect::set(&descriptor, &"configurable".into(), &JsValue::TRUE).unwrap();
+    Object::define_property(
+        &window().unwrap().navigator(),
+        &"clipboard".into(),
+        &descriptor,
+    );
+
+    let document = window().unwrap().document().unwrap();
+    let canvas: HtmlCanvasElement = document.create_element("canvas").unwrap().unchecked_into();
+    document.body().unwrap().append_child(&canvas).unwrap();
+    let context = canvas.get_context("2d").unwrap().unwrap().unchecked_into();
+    let mut backend = CanvasBackend::new(context, io::sink());
+    let events = Rc::new(RefCell::new(Vec::new()));
+    backend.on_event({
+        let events = Rc::clone(&events);
+        move |event| events.borrow_mut().push(event)
+    });
+    canvas.focus().unwrap();
+    next_task().await;
+    events.borrow_mut().clear();
+
+    // Outside of user input the copy itself may be refused, which does not matter here.
+    let _ = backend.write_clipboard("copied").await;
+    next_task().await;
+    assert_eq!(document.active_element(), Some(canvas.clone().into()));
+    assert!(
+        !events
+            .borrow()
+            .iter()
+            .any(|event| matches!(event, Event::FocusGained | Event::FocusLost)),
+        "{:?}",
+        events.borrow()
+    );
+
+    drop(backend);
+    canvas.remove();
+}
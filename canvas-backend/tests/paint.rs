@@ -0,0 +1,161 @@
+//! What the backend paints on its canvas.
+//!
+//! Run in a browser with `wasm-pack test --headless --chrome canvas-backend`.
+
+#![cfg(target_arch = "wasm32")]
+
+use std::io::{self, Write};
+
+use canvas_backend::{CanvasBackend, CellGeometry, Theme};
+use js_sys::{Object, Reflect};
+use ratatui::{backend::Backend, buffer::Cell, style::Color};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+use web_sys::{window, CanvasRenderingContext2d, HtmlCanvasElement};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+/// Makes the window report `ratio` as its device pixel ratio.
+fn set_device_pixel_ratio(ratio: f64) {
+    let descriptor = Object::new();
+    Reflect::set(&descriptor, &"value".into(), &ratio.into()).unwrap();
+    Reflect::set(&descriptor, &"configurable".into(), &JsValue::TRUE).unwrap();
+    Object::define_property(&window().unwrap(), &"devicePixelRatio".into(), &descriptor);
+}
+
+/// Creates a canvas of 317 x 211 CSS pixels on the page and a 2D context for it.
+fn canvas() -> (HtmlCanvasElement, CanvasRenderingContext2d) {
+    let document = window().unwrap().document().unwrap();
+    let canvas: HtmlCanvasElement = document.create_element("canvas").unwrap().unchecked_into();
+    canvas
+        .set_attribute("style", "width: 317px; height: 211px")
+        .unwrap();
+    document.body().unwrap().append_child(&canvas).unwrap();
+    let context = canvas.get_context("2d").unwrap().unwrap().unchecked_into();
+    (canvas, context)
+}
+
+/// Returns the RGBA pixels of the device pixel rectangle from (`left`, `top`) to (`right`,
+/// `bottom`).
+fn pixels(
+    context: &CanvasRenderingContext2d,
+    left: f64,
+    top: f64,
+    right: f64,
+    bottom: f64,
+) -> Vec<u8> {
+    context
+        .get_image_data(left, top, right - left, bottom - top)
+        .unwrap()
+        .data()
+        .0
+}
+
+#[wasm_bindgen_test]
+fn backgrounds_have_no_seams() {
+    for ratio in [1.0, 1.25, 1.5, 1.75, 2.25] {
+        set_device_pixel_ratio(ratio);
+        let (canvas, context) = canvas();
+        let mut backend = CanvasBackend::new(context.clone(), io::sink());
+        backend.set_cell_geometry(CellGeometry::Fixed {
+            width: 7.3,
+            height: 13.7,
+        });
+
+        // Leave out the first row and column, which the cursor sits on.
+        let area = backend.size().unwrap();
+        let mut cell = Cell::default();
+        cell.set_bg(Color::Rgb(200, 40, 40));
+        let cells: Vec<_> = (1..area.width)
+            .flat_map(|x| (1..area.height).map(move |y| (x, y)))
+            .map(|(x, y)| (x, y, &cell))
+            .collect();
+        backend.draw(cells.into_iter()).unwrap();
+
+        let metrics = backend.metrics();
+        let snap = |v: f64| (v * ratio).round();
+        let (left, top) = (snap(metrics.width), snap(metrics.height));
+        let right = snap(metrics.width * f64::from(area.width));
+        let bottom = snap(metrics.height * f64::from(area.height));
+        let pixels = pixels(&context, left, top, right, bottom);
+        let first = &pixels[..4];
+        let seams = pixels
+            .chunks_exact(4)
+            .filter(|pixel| pixel != &first)
+            .count();
+        assert_eq!(
+            seams, 0,
+            "{seams} seam pixels at a device pixel ratio of {ratio}"
+        );
+
+        drop(backend);
+        canvas.remove();
+    }
+}
+
+#[wasm_bindgen_test]
+fn repaints_keep_wide_graphemes_whole() {
+    set_device_pixel_ratio(1.0);
+    let (canvas, context) = canvas();
+    let mut backend = CanvasBackend::new(context.clone(), io::sink());
+    backend.hide_cursor().unwrap();
+
+    // Like ratatui, draw the wide grapheme but not the blank cell it covers.
+    let mut cell = Cell::default();
+    cell.set_symbol("\u{6f22}");
+    backend.draw([(0, 0, &cell)].into_iter()).unwrap();
+
+    let metrics = backend.metrics();
+    let right_half = || {
+        pixels(
+            &context,
+            metrics.width.round(),
+            0.0,
+            (metrics.width * 2.0).round(),
+            metrics.height.round(),
+        )
+    };
+    let drawn = right_half();
+    let background = &drawn[..4];
+    assert!(
+        drawn.chunks_exact(4).any(|pixel| pixel != background),
+        "the grapheme reaches into its second cell"
+    );
+
+    backend.set_theme(Theme::default());
+    assert_eq!(right_half(), drawn);
+
+    drop(backend);
+    canvas.remove();
+}
+
+#[wasm_bindgen_test]
+fn output_scrolling_at_once_paints_like_line_by_line() {
+    set_device_pixel_ratio(1.0);
+    let lines: Vec<String> = (0..100)
+        .map(|n| format!("\x1b[3{}mline {n}\n", n % 8))
+        .collect();
+    let paint = |writes: &[&[u8]]| {
+        let (canvas, context) = canvas();
+        let mut backend = CanvasBackend::new(context.clone(), io::sink());
+        backend.set_vt_parser(true);
+        backend.hide_cursor().unwrap();
+        for bytes in writes {
+            backend.write_all(bytes).unwrap();
+        }
+        let painted = pixels(
+            &context,
+            0.0,
+            0.0,
+            f64::from(canvas.width()),
+            f64::from(canvas.height()),
+        );
+        drop(backend);
+        canvas.remove();
+        painted
+    };
+
+    let output = lines.concat();
+    let line_by_line: Vec<&[u8]> = lines.iter().map(|line| line.as_bytes()).collect();
+    assert!(paint(&[output.as_bytes()]) == paint(&line_by_line));
+}
@@ -1,16 +1,22 @@
-use canvas_backend::CanvasBackend;
+use canvas_backend::{
+    event::{Event, KeyCode, KeyEventKind},
+    CanvasBackend,
+};
 use ratatui::{
     prelude::*,
     symbols::scrollbar,
     widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
 };
 
+use futures::{select, StreamExt};
+use gloo_timers::future::IntervalStream;
 use std::{
     cell::RefCell,
     io::{self, Stdout},
     rc::Rc,
 };
-use wasm_bindgen::prelude::{wasm_bindgen, Closure, JsCast};
+use wasm_bindgen::prelude::{wasm_bindgen, JsCast};
+use wasm_bindgen_futures::spawn_local;
 use web_sys::{console, window, CanvasRenderingContext2d, HtmlCanvasElement};
 
 #[derive(Default)]
@@ -64,58 +70,50 @@ fn run_app(
         terminal.draw(|f| ui(f, &mut app)).unwrap();
     }
 
-    // key events
-    {
-        let app = Rc::clone(&app);
-        let terminal = Rc::clone(&terminal);
-        let func = Box::new(move |event: web_sys::KeyboardEvent| {
+    // events and ticks, awaited in a loop like with `event::read()` natively
+    let mut events = (*terminal).borrow().backend().event_stream();
+    spawn_local(async move {
+        let mut tick = IntervalStream::new(250).fuse();
+        loop {
+            select! {
+                event = events.next() => match event {
+                    Some(Event::Key(key)) if key.kind != KeyEventKind::Release => {
+                        let mut app = (*app).borrow_mut();
+                        match key.code {
+                            KeyCode::Char('j') => {
+                                app.vertical_scroll = app.vertical_scroll.saturating_add(1);
+                                app.vertical_scroll_state =
+                                    app.vertical_scroll_state.position(app.vertical_scroll);
+                            }
+                            KeyCode::Char('k') => {
+                                app.vertical_scroll = app.vertical_scroll.saturating_sub(1);
+                                app.vertical_scroll_state =
+                                    app.vertical_scroll_state.position(app.vertical_scroll);
+                            }
+                            KeyCode::Char('h') => {
+                                app.horizontal_scroll = app.horizontal_scroll.saturating_sub(1);
+                                app.horizontal_scroll_state =
+                                    app.horizontal_scroll_state.position(app.horizontal_scroll);
+                            }
+                            KeyCode::Char('l') => {
+                                app.horizontal_scroll = app.horizontal_scroll.saturating_add(1);
+                                app.horizontal_scroll_state =
+                                    app.horizontal_scroll_state.position(app.horizontal_scroll);
+                            }
+                            code => console::log_1(&format!("key={code:?}").into()),
+                        }
+                    }
+                    Some(_) => {}
+                    None => break,
+                },
+                _ = tick.next() => {}
+            }
             let mut app = (*app).borrow_mut();
             let mut terminal = (*terminal).borrow_mut();
-            terminal.clear().unwrap();
-
-            match event.key().as_str() {
-                "j" => {
-                    app.vertical_scroll = app.vertical_scroll.saturating_add(1);
-                    app.vertical_scroll_state =
-                        app.vertical_scroll_state.position(app.vertical_scroll);
-                }
-                "k" => {
-                    app.vertical_scroll = app.vertical_scroll.saturating_sub(1);
-                    app.vertical_scroll_state =
-                        app.vertical_scroll_state.position(app.vertical_scroll);
-                }
-                "h" => {
-                    app.horizontal_scroll = app.horizontal_scroll.saturating_sub(1);
-                    app.horizontal_scroll_state =
-                        app.horizontal_scroll_state.position(app.horizontal_scroll);
-                }
-                "l" => {
-                    app.horizontal_scroll = app.horizontal_scroll.saturating_add(1);
-                    app.horizontal_scroll_state =
-                        app.horizontal_scroll_state.position(app.horizontal_scroll);
-                }
-                key => console::log_1(&format!("key={key}").into()),
-            }
             terminal.draw(|f| ui(f, &mut app)).unwrap();
-        });
-
-        let closure = Closure::wrap(func as Box<dyn FnMut(_)>);
-        let document = window().unwrap().document().unwrap();
-        document
-            .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
-            .unwrap();
-        closure.forget();
-    }
+        }
+    });
 
-    // if let Event::Key(key) = event::read()? {
-    //     if key.kind == KeyEventKind::Press {
-    //         match key.code {
-    //             KeyCode::Char('q') => return Ok(()),
-    //             KeyCode::Char('p') => app.show_popup = !app.show_popup,
-    //             _ => {}
-    //         }
-    //     }
-    // }
     Ok(())
 }
 
@@ -1,4 +1,7 @@
-use canvas_backend::CanvasBackend;
+use canvas_backend::{
+    event::{Event, KeyCode, KeyEventKind},
+    CanvasBackend,
+};
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, Cell as TuiCell, Row, Table, TableState},
@@ -191,29 +194,35 @@ fn run_app(
         terminal.draw(|f| ui(f, &mut app)).unwrap();
     }
 
-    // key events
+    // key events, reported while the canvas has focus
     {
         let app = Rc::clone(&app);
-        let terminal = Rc::clone(&terminal);
-        let func = Box::new(move |event: web_sys::KeyboardEvent| {
-            let mut app = (*app).borrow_mut();
-            let mut terminal = (*terminal).borrow_mut();
-            terminal.clear().unwrap();
-
-            match event.key().as_str() {
-                "ArrowDown" => app.next(),
-                "ArrowUp" => app.previous(),
-                key => console::log_1(&format!("key={key}").into()),
-            }
-            terminal.draw(|f| ui(f, &mut app)).unwrap();
-        });
+        // The backend owns the handler, so a strong reference would keep the terminal alive.
+        let handle = Rc::downgrade(&terminal);
+        (*terminal)
+            .borrow_mut()
+            .backend_mut()
+            .on_event(move |event| {
+                let Event::Key(key) = event else {
+                    return;
+                };
+                if key.kind == KeyEventKind::Release {
+                    return;
+                }
+                let Some(terminal) = handle.upgrade() else {
+                    return;
+                };
+                let mut app = (*app).borrow_mut();
+                let mut terminal = (*terminal).borrow_mut();
+                terminal.clear().unwrap();
 
-        let closure = Closure::wrap(func as Box<dyn FnMut(_)>);
-        let document = window().unwrap().document().unwrap();
-        document
-            .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
-            .unwrap();
-        closure.forget();
+                match key.code {
+                    KeyCode::Down => app.next(),
+                    KeyCode::Up => app.previous(),
+                    code => console::log_1(&format!("key={code:?}").into()),
+                }
+                terminal.draw(|f| ui(f, &mut app)).unwrap();
+            });
     }
 
     // resize events